@@ -1,8 +1,18 @@
 use std::{fmt::Display, num::ParseFloatError};
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 use crate::{
     environment::Environment,
-    interpreter::{process_statements, visitor::NodeVisitor},
+    function::Function,
+    interpreter::{
+        errors::{InterpreterError, InterpreterErrorKind},
+        interpreter::{declare_functions, run_statements},
+        natives::NativeFunction,
+        visitor::NodeVisitor,
+    },
+    object::Object,
 };
 
 /// Trait representing an AST node that can accept a visitor.
@@ -16,19 +26,48 @@ pub trait Node {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Expression {
     NumberLiteral(f64),
     Boolean(bool),
+    Null,
+    Undefined,
     Identifier(String),
     String(String),
     Prefix(PrefixOperator, Box<Expression>),
+    // x++ and x--, which change the variable but produce its old value
+    Postfix(Box<Expression>, PostfixOperator),
     Operation(Box<Expression>, Operator, Box<Expression>),
-    // Although this allows the left side to be any expression, the interpreter will only accept Identifier(String) that have been defined
+    // Although this allows the left side to be any expression, the interpreter will only accept defined identifiers, properties and elements
     Assignment(Box<Expression>, Box<Expression>),
-    Call(Box<Expression>, Vec<Expression>)
+    Call(Box<Expression>, Vec<Expression>),
+    Array(Vec<Expression>),
+    // Only produced inside call arguments and array literals, where the array is expanded in place
+    Spread(Box<Expression>),
+    // object.property
+    Member(Box<Expression>, String),
+    // object[index]
+    Index(Box<Expression>, Box<Expression>),
+    // { key: value }, with keys kept in the order they were written
+    Object(Vec<(String, Expression)>),
+    // target ||= value, target &&= value and target ??= value, which only assign when the operator would use the right side
+    LogicalAssignment(Box<Expression>, Operator, Box<Expression>),
+    // target += value and the other arithmetic assignments, which find the target once so arr[i++] += 1 increments i once
+    CompoundAssignment(Box<Expression>, Operator, Box<Expression>),
+    // condition ? consequent : alternate, which only evaluates the branch it picks
+    Conditional(Box<Expression>, Box<Expression>, Box<Expression>),
+    // the object a method was called on, or undefined outside of a method call
+    This,
+    // a function created as a value, such as the method in { name(parameters) { ... } }
+    Function(String, Vec<Expression>, Block),
+    // an arrow function's expression body is kept as a block returning it
+    ArrowFunction(Vec<Expression>, Block),
+    // the comma operator, a, b, c, which evaluates each in order and gives the last
+    Sequence(Vec<Expression>)
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Statement {
     Let(String, Expression),
     FunctionDeclaration(String, Vec<Expression>, Block),
@@ -36,10 +75,38 @@ pub enum Statement {
     ExpressionStatement(Expression),
     ReturnStatement(Option<Expression>),
     // Although this allows any statement, a while statement specifically should only be constructed with a conditional
-    While(Box<Statement>)
+    While(Box<Statement>),
+    // for (let identifier of expression) block
+    ForOf(String, Expression, Block),
+    // let [a, { b }] = expression
+    LetPattern(Pattern, Expression),
+    // for (init; condition; update) block, where any of the three sections may be left empty
+    For(Option<Box<Statement>>, Option<Expression>, Option<Expression>, Block),
+    Break,
+    Continue,
+    // { statements }, which at the start of a statement is always a block rather than an object literal
+    Block(Block),
+    // try block catch (identifier) block finally block, where the identifier is optional
+    // and either the catch or the finally may be left out, but not both
+    Try(Block, Option<(Option<String>, Block)>, Option<Block>),
+    // throw expression, which ends every statement up to the nearest enclosing try
+    Throw(Expression),
+    // switch (discriminant) { case test: statements default: statements }, where the default case has no test
+    Switch(Expression, Vec<(Option<Expression>, Vec<Statement>)>)
+}
+
+// The left side of a destructuring declaration, which can nest arrays and objects inside each other
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Pattern {
+    Identifier(String),
+    Array(Vec<Pattern>),
+    // { key: pattern }, where the shorthand { key } is { key: key }
+    Object(Vec<(String, Pattern)>)
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Operator {
     Add,
     Subtract,
@@ -48,31 +115,110 @@ pub enum Operator {
     Equal,
     LessThan,
     GreaterThan,
+    LessThanOrEqual,
+    GreaterThanOrEqual,
     And,
     Or,
     Exponentiation,
-    Modulo
+    Modulo,
+    NullishCoalescing,
+    // key in object
+    In
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum PrefixOperator {
     Increment,
     Decrement,
     Negative,
     Positive,
-    Not
+    Not,
+    TypeOf,
+    Delete
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PostfixOperator {
+    Increment,
+    Decrement
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ExpressionResult {
     Number(f64),
     String(String),
     Boolean(bool),
+    Array(Vec<ExpressionResult>),
+    Object(Object),
+    // natives are Rust function pointers, which have no serialized form
+    #[cfg_attr(feature = "serde", serde(skip))]
+    NativeFunction(NativeFunction),
+    // a function declared in the script along with the name it was declared under
+    Function(String, Function),
+    Null,
     Undefined
 }
 
+/// How a statement ended when it stopped running the statements after it.
+/// A return carries its value out of the function, a break ends the loop it is in,
+/// a continue skips to the loop's next iteration, and a throw carries an error out until a `try` catches it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Completion {
+    Return(ExpressionResult),
+    Break,
+    Continue,
+    Throw(Thrown)
+}
+
+/// What a throw carries until a `catch` receives it: either the value a script threw,
+/// or the message of an error the interpreter raised itself, like `Uncaught TypeError: x is not a function`.
+/// The value is boxed so that results carrying it stay small, as they are passed through every nested call
+#[derive(Clone, Debug, PartialEq)]
+pub enum Thrown {
+    Value(Box<ExpressionResult>),
+    Error(String),
+}
+
+impl Thrown {
+    // How the throw is shown once nothing caught it
+    pub fn uncaught_message(&self) -> String {
+        match self {
+            Thrown::Value(value) => InterpreterError {
+                kind: InterpreterErrorKind::Thrown((**value).clone()),
+            }
+            .to_string(),
+            Thrown::Error(message) => message.clone(),
+        }
+    }
+
+    // The value a catch binds: what the script threw, unchanged, or the error's message without the "Uncaught " prefix
+    pub fn caught_value(&self) -> ExpressionResult {
+        match self {
+            Thrown::Value(value) => (**value).clone(),
+            Thrown::Error(message) => {
+                ExpressionResult::String(message.strip_prefix("Uncaught ").unwrap_or(message).to_string())
+            }
+        }
+    }
+}
+
+impl From<String> for Thrown {
+    fn from(message: String) -> Self {
+        Thrown::Error(message)
+    }
+}
+
+impl From<&str> for Thrown {
+    fn from(message: &str) -> Self {
+        Thrown::Error(message.to_string())
+    }
+}
+
 impl Node for Expression {
-    type Output = Result<ExpressionResult, String>;
+    type Output = Result<ExpressionResult, Thrown>;
 
     fn accept(&self, visitor: &mut dyn NodeVisitor) -> Self::Output {
         visitor.visit_expression(self)
@@ -80,7 +226,7 @@ impl Node for Expression {
 }
 
 impl Node for Statement {
-    type Output = Option<ExpressionResult>;
+    type Output = Option<Completion>;
 
     fn accept(&self, visitor: &mut dyn NodeVisitor) -> Self::Output {
         visitor.visit_statement(self)
@@ -97,31 +243,146 @@ impl ExpressionResult {
     pub fn coerce_to_bool(&self) -> bool {
         match self {
             ExpressionResult::Boolean(val) => *val,
-            ExpressionResult::Number(val) => *val != 0.0,
+            ExpressionResult::Number(val) => *val != 0.0 && !val.is_nan(),
             ExpressionResult::String(val) => val.len() > 0,
+            // every array and object is truthy, even when empty
+            ExpressionResult::Array(_) => true,
+            ExpressionResult::Object(_) => true,
+            ExpressionResult::NativeFunction(_) | ExpressionResult::Function(_, _) => true,
+            ExpressionResult::Null => false,
             ExpressionResult::Undefined => false
         }
     }
 
+    /// The type name `typeof` gives for this value
+    pub fn type_of(&self) -> &'static str {
+        match self {
+            ExpressionResult::Boolean(_) => "boolean",
+            ExpressionResult::Number(_) => "number",
+            ExpressionResult::String(_) => "string",
+            // null reports as an object for historical reasons
+            ExpressionResult::Array(_) | ExpressionResult::Object(_) | ExpressionResult::Null => "object",
+            ExpressionResult::NativeFunction(_) | ExpressionResult::Function(_, _) => "function",
+            ExpressionResult::Undefined => "undefined"
+        }
+    }
+
+    pub fn is_nullish(&self) -> bool {
+        matches!(self, ExpressionResult::Null | ExpressionResult::Undefined)
+    }
+
+    pub fn is_nan(&self) -> bool {
+        matches!(self, ExpressionResult::Number(val) if val.is_nan())
+    }
+
     pub fn coerce_to_number(&self) -> Result<f64, ParseFloatError> {
         match self {
             ExpressionResult::Boolean(val) => if *val {Ok(1.0)} else {Ok(0.0)},
             ExpressionResult::Number(val) => Ok(*val),
             ExpressionResult::String(val) => val.parse::<f64>(),
-            ExpressionResult::Undefined => "undefined".parse::<f64>()
+            // arrays convert through their string form, so [5] is 5 while [1, 2] is NaN
+            ExpressionResult::Array(_) => self.coerce_to_string().parse::<f64>(),
+            ExpressionResult::Object(_) => self.coerce_to_string().parse::<f64>(),
+            ExpressionResult::NativeFunction(_) | ExpressionResult::Function(_, _) => "function".parse::<f64>(),
+            // null is 0 while undefined is NaN, so null + 1 is 1 and undefined + 1 is NaN
+            ExpressionResult::Null => Ok(0.0),
+            ExpressionResult::Undefined => Ok(f64::NAN)
+        }
+    }
+
+    /// Converts a value the way Number() does, so anything that isn't a number is NaN rather than an error
+    pub fn to_number(&self) -> f64 {
+        match self {
+            ExpressionResult::String(_) | ExpressionResult::Array(_) | ExpressionResult::Object(_) => {
+                string_to_number(&self.coerce_to_string())
+            }
+            _ => self.coerce_to_number().unwrap_or(f64::NAN),
         }
     }
 
     pub fn coerce_to_string(&self) -> String {
         match self {
             ExpressionResult::Boolean(val) => if *val { "true".to_string() } else { "false".to_string() },
-            ExpressionResult::Number(val) => val.to_string(),
+            ExpressionResult::Number(val) => number_to_string(*val),
             ExpressionResult::String(val) => val.to_string(),
+            ExpressionResult::Array(elements) => join_elements(elements, ","),
+            ExpressionResult::Object(_) => "[object Object]".to_string(),
+            ExpressionResult::NativeFunction(function) => format!("{:?}", function),
+            ExpressionResult::Function(name, function) => function.source(name),
+            ExpressionResult::Null => "null".to_string(),
             ExpressionResult::Undefined => "undefined".to_string()
         }
     }
 }
 
+/// Reads a string the way Number() does.  Surrounding whitespace is ignored and an empty string is 0,
+/// while Rust spellings such as "inf" and "NaN" are rejected, leaving "Infinity" as the only infinite one.
+pub fn string_to_number(string: &str) -> f64 {
+    let trimmed = string.trim();
+    if trimmed.is_empty() {
+        return 0.0;
+    }
+    let (sign, unsigned) = match trimmed.strip_prefix('-') {
+        Some(rest) => (-1.0, rest),
+        None => (1.0, trimmed.strip_prefix('+').unwrap_or(trimmed)),
+    };
+    if unsigned == "Infinity" {
+        return sign * f64::INFINITY;
+    }
+    // anything else has to start with a digit or a decimal point, which also rules out a second sign
+    if !unsigned.starts_with(|character: char| character.is_ascii_digit() || character == '.') {
+        return f64::NAN;
+    }
+    unsigned.parse::<f64>().map_or(f64::NAN, |number| sign * number)
+}
+
+/// Writes a number the way JS does.  Rust spells infinity "inf" and never uses exponents, while JS
+/// writes 1e21 and above, and anything below 1e-6, in exponential notation, such as "1e+21" and "1e-7".
+pub fn number_to_string(number: f64) -> String {
+    if number.is_nan() {
+        return "NaN".to_string();
+    }
+    if number == 0.0 {
+        return "0".to_string();
+    }
+    if number.is_infinite() {
+        return if number > 0.0 { "Infinity".to_string() } else { "-Infinity".to_string() };
+    }
+    if number < 0.0 {
+        return format!("-{}", number_to_string(-number));
+    }
+    // the shortest digits that read back as the same number, with the decimal point n digits in
+    let exponential = format!("{:e}", number);
+    let (mantissa, exponent) = exponential.split_once('e').unwrap();
+    let digits = mantissa.replace('.', "");
+    let k = digits.len() as i32;
+    let n = exponent.parse::<i32>().unwrap() + 1;
+    if k <= n && n <= 21 {
+        format!("{}{}", digits, "0".repeat((n - k) as usize))
+    } else if 0 < n && n <= 21 {
+        format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{}", "0".repeat(-n as usize), digits)
+    } else {
+        let sign = if n > 0 { "+" } else { "-" };
+        let fraction = if k == 1 { String::new() } else { format!(".{}", &digits[1..]) };
+        format!("{}{}e{}{}", &digits[..1], fraction, sign, (n - 1).abs())
+    }
+}
+
+/// Joins array elements as strings, as `array.join(separator)` does.
+/// Null and undefined elements are written as empty strings, and nested arrays join with commas.
+pub fn join_elements(elements: &[ExpressionResult], separator: &str) -> String {
+    elements
+        .iter()
+        .map(|element| match element {
+            ExpressionResult::Null | ExpressionResult::Undefined => String::new(),
+            _ => element.coerce_to_string(),
+        })
+        .collect::<Vec<String>>()
+        .join(separator)
+}
+
 impl Statement {
     pub fn into_block(self) -> Block {
         Block::new(vec![self])
@@ -133,6 +394,7 @@ impl Statement {
 
 // A Block is a Vec of statements and a list of blocks this block contains
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Block {
     statements: Vec<Statement>,
 }
@@ -144,7 +406,69 @@ impl Block {
         }
     }
 
-    pub fn execute_block(&self, environment: &mut Environment) -> Result<ExpressionResult, String> {
-        return Ok(process_statements(self.statements.clone(), environment));
+    pub fn statements(&self) -> &[Statement] {
+        &self.statements
     }
-}
\ No newline at end of file
+
+    // Runs the block as a function body, where an error the body doesn't catch is thrown to the caller
+    pub fn execute_block(&self, environment: &mut Environment) -> Result<ExpressionResult, Thrown> {
+        match self.run(environment) {
+            Some(Completion::Return(value)) => Ok(value),
+            Some(Completion::Throw(error)) => Err(error),
+            _ => Ok(ExpressionResult::Undefined),
+        }
+    }
+
+    // Runs the block inside a loop or conditional, where a break has to reach the enclosing loop
+    pub fn run(&self, environment: &mut Environment) -> Option<Completion> {
+        declare_functions(&self.statements, environment);
+        run_statements(&self.statements, environment)
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_array_should_coerce_to_true() {
+        assert!(ExpressionResult::Array(vec![]).coerce_to_bool());
+    }
+
+    #[test]
+    fn non_empty_array_should_coerce_to_true() {
+        assert!(ExpressionResult::Array(vec![ExpressionResult::Boolean(false)]).coerce_to_bool());
+    }
+
+    #[test]
+    fn empty_object_should_coerce_to_true() {
+        assert!(ExpressionResult::Object(Object::new()).coerce_to_bool());
+    }
+
+    #[test]
+    fn non_empty_object_should_coerce_to_true() {
+        let mut object = Object::new();
+        object.set("a".into(), ExpressionResult::Number(0.0));
+        assert!(ExpressionResult::Object(object).coerce_to_bool());
+    }
+
+    #[test]
+    fn nan_should_coerce_to_false() {
+        assert!(!ExpressionResult::Number(f64::NAN).coerce_to_bool());
+    }
+
+    #[test]
+    fn numbers_should_convert_to_strings_like_js() {
+        let string = |number: f64| ExpressionResult::Number(number).coerce_to_string();
+        assert_eq!(string(10.0), "10");
+        assert_eq!(string(-0.0), "0");
+        assert_eq!(string(123.456), "123.456");
+        assert_eq!(string(0.1 + 0.2), "0.30000000000000004");
+        assert_eq!(string(100000000000000000000.0), "100000000000000000000");
+        assert_eq!(string(1e21), "1e+21");
+        assert_eq!(string(1.5e300), "1.5e+300");
+        assert_eq!(string(0.000001), "0.000001");
+        assert_eq!(string(0.0000001), "1e-7");
+        assert_eq!(string(-1.25e-7), "-1.25e-7");
+        assert_eq!(string(f64::NEG_INFINITY), "-Infinity");
+    }
+}
@@ -1,17 +1,49 @@
 use std::collections::{HashMap, HashSet};
 use crate::ast::ExpressionResult;
-use crate::function::Function;
+use crate::function::{Function, MAX_CALL_DEPTH};
+use crate::interpreter::natives::register_natives;
+use crate::interpreter::output::Output;
 
 #[derive(Clone)]
 pub struct Environment {
     pub variables: HashMap<String, (bool, ExpressionResult)>,
     pub functions: HashMap<String, Function>,
-    modified_inherited_variables: HashSet<String>
+    modified_inherited_variables: HashSet<String>,
+    pub output: Output,
+    // how many function calls deep this environment is, so runaway recursion can be stopped
+    pub(crate) call_depth: usize,
+    // how deep calls may nest before a RangeError.  The default fits the stack of the thread `run` evaluates on,
+    // but not a default thread's, so evaluating without `run` on such a thread needs a lower limit
+    pub max_call_depth: usize,
+    // the value of the most recent expression statement run directly in this environment
+    pub(crate) last_value: ExpressionResult,
+    // what `this` refers to, which is the object when running a method and undefined otherwise
+    pub(crate) this: ExpressionResult,
+    // variables declared while running a function, which function values created there capture
+    pub(crate) function_scope_variables: HashSet<String>,
+    // globals like NaN that assignments can't change, although a `let` of the same name still shadows them
+    read_only_variables: HashSet<String>
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Environment {
     pub fn new() -> Self {
-        Environment { variables: HashMap::new(), functions: HashMap::new(), modified_inherited_variables: HashSet::new() }
+        let mut env = Environment { variables: HashMap::new(), functions: HashMap::new(), modified_inherited_variables: HashSet::new(), output: Output::stdout(), call_depth: 0, max_call_depth: MAX_CALL_DEPTH, last_value: ExpressionResult::Undefined, this: ExpressionResult::Undefined, function_scope_variables: HashSet::new(), read_only_variables: HashSet::new() };
+        register_natives(&mut env);
+        for (identifier, value) in [
+            ("NaN", ExpressionResult::Number(f64::NAN)),
+            ("Infinity", ExpressionResult::Number(f64::INFINITY)),
+            ("undefined", ExpressionResult::Undefined),
+        ] {
+            env.define_variable(identifier.to_string(), value);
+            env.read_only_variables.insert(identifier.to_string());
+        }
+        env
     }
 
     pub fn get_variable(&self, identifier: &str) -> Option<ExpressionResult> {
@@ -22,10 +54,18 @@ impl Environment {
     }
 
     pub fn define_variable(&mut self, identifier: String, value: ExpressionResult) {
+        if self.call_depth > 0 {
+            self.function_scope_variables.insert(identifier.clone());
+        }
+        self.read_only_variables.remove(&identifier);
         self.variables.insert(identifier, (false, value));
     }
 
     pub fn set_variable(&mut self, identifier: String, value: ExpressionResult) {
+        // like sloppy mode JS, assigning to a read only global quietly does nothing
+        if self.read_only_variables.contains(&identifier) {
+            return;
+        }
         let inherited = self.is_variable_greater_scope(&identifier);
         if inherited {
             self.modified_inherited_variables.insert(identifier.clone());
@@ -56,6 +96,25 @@ impl Environment {
         self.functions.contains_key(&identifier)
     }
 
+    /// Lists every variable with its value and then every declared function, each sorted by name
+    pub fn dump(&self) -> String {
+        let mut variables: Vec<(&String, &ExpressionResult)> = self
+            .variables
+            .iter()
+            .map(|(identifier, (_, value))| (identifier, value))
+            .collect();
+        variables.sort_by(|a, b| a.0.cmp(b.0));
+        let mut functions: Vec<&String> = self.functions.keys().collect();
+        functions.sort();
+
+        let mut lines: Vec<String> = variables
+            .into_iter()
+            .map(|(identifier, value)| format!("{} = {}", identifier, value))
+            .collect();
+        lines.extend(functions.into_iter().map(|identifier| format!("function {}", identifier)));
+        lines.join("\n")
+    }
+
     pub fn create_child_env(&mut self) -> Environment {
         let mut child_env = self.clone();
         for (inherited, _) in child_env.variables.values_mut() {
@@ -106,4 +165,28 @@ mod tests {
         let env = Environment::new();
         assert_eq!(env.has_variable("x".to_string()), false);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn dump_should_list_variables_and_functions() {
+        let mut env = Environment::new();
+        env.define_variable("x".to_string(), ExpressionResult::Number(5.0));
+        env.set_function("add".to_string(), Function::new(vec![], crate::ast::Block::new(vec![])));
+        let dump = env.dump();
+        assert!(dump.contains("x = 5\n"));
+        assert!(dump.ends_with("function add"));
+    }
+
+    #[test]
+    fn it_should_ignore_assignments_to_read_only_globals_until_shadowed() {
+        let mut env = Environment::new();
+        env.set_variable("Infinity".to_string(), ExpressionResult::Number(1.0));
+        assert_eq!(env.get_variable("Infinity"), Some(ExpressionResult::Number(f64::INFINITY)));
+
+        let mut child_env = env.create_child_env();
+        child_env.define_variable("Infinity".to_string(), ExpressionResult::Number(1.0));
+        child_env.set_variable("Infinity".to_string(), ExpressionResult::Number(2.0));
+        assert_eq!(child_env.get_variable("Infinity"), Some(ExpressionResult::Number(2.0)));
+        env.merge_child_env(child_env);
+        assert_eq!(env.get_variable("Infinity"), Some(ExpressionResult::Number(f64::INFINITY)));
+    }
+}
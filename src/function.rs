@@ -1,37 +1,112 @@
-use crate::ast::{Block, Expression, ExpressionResult};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::ast::{Block, Expression, ExpressionResult, Statement, Thrown};
 use crate::environment::Environment;
-use crate::interpreter::{eval_expression};
+use crate::interpreter::errors::{InterpreterError, InterpreterErrorKind};
+
+// Each call in the script uses several frames of the Rust stack, so calls are limited before it runs out.
+// A thousand calls needs more stack than a default thread has, so it is only safe through `run`, which
+// evaluates on a thread of INTERPRETER_STACK_SIZE.  This is the default for Environment::max_call_depth.
+pub const MAX_CALL_DEPTH: usize = 1000;
+
+/// Stack size of the thread `run` evaluates on, large enough to reach MAX_CALL_DEPTH in a debug build
+pub const INTERPRETER_STACK_SIZE: usize = 256 * 1024 * 1024;
 
 // A Function consists of its arguments, and block to be executed after setting the environment up from arguments
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Function {
     arguments: Vec<Expression>,
-    block: Block
+    block: Block,
+    // arrow functions keep the `this` of where they were created instead of taking the caller's
+    lexical_this: Option<Box<ExpressionResult>>,
+    // the values of the enclosing function's variables when this function was created, so it can
+    // still read them once that function has returned.  Globals aren't captured and are read when called.
+    // Boxed to keep function values small, as every ExpressionResult is as large as its largest variant
+    captured: Box<[(String, ExpressionResult)]>
 }
 
 impl Function {
     pub fn new(arguments: Vec<Expression>, block: Block) -> Self {
         Function {
             arguments,
-            block
+            block,
+            lexical_this: None,
+            captured: Box::new([])
         }
     }
 
-    pub fn call(&self, arguments: Vec<Expression>, parent_env: &mut Environment) -> Result<ExpressionResult, String> {
+    pub fn arrow(arguments: Vec<Expression>, block: Block, this: ExpressionResult) -> Self {
+        Function {
+            arguments,
+            block,
+            lexical_this: Some(Box::new(this)),
+            captured: Box::new([])
+        }
+    }
+
+    /// Captures the variables of the function call this function is being created in, making it a closure
+    pub fn capturing(mut self, env: &Environment) -> Self {
+        self.captured = env
+            .function_scope_variables
+            .iter()
+            .filter_map(|identifier| Some((identifier.clone(), env.get_variable(identifier)?)))
+            .collect();
+        self
+    }
+
+    pub fn parameter_count(&self) -> usize {
+        self.arguments.len()
+    }
+
+    // The source isn't kept after parsing, so it is printed back from the declaration instead
+    pub fn source(&self, name: &str) -> String {
+        if self.lexical_this.is_some() {
+            return Expression::ArrowFunction(self.arguments.clone(), self.block.clone()).to_string();
+        }
+        Statement::FunctionDeclaration(name.to_string(), self.arguments.clone(), self.block.clone()).to_string()
+    }
+
+    // Arguments arrive already evaluated (and with any spread arguments flattened) by the caller.
+    // A plain call doesn't pass on the caller's `this`, so it is undefined inside the function
+    pub fn call(&self, arguments: Vec<ExpressionResult>, parent_env: &mut Environment) -> Result<ExpressionResult, Thrown> {
+        self.call_with_this(ExpressionResult::Undefined, arguments, parent_env)
+    }
+
+    // Calls the function as a method of `this`, as in object.method()
+    pub fn call_with_this(
+        &self,
+        this: ExpressionResult,
+        arguments: Vec<ExpressionResult>,
+        parent_env: &mut Environment,
+    ) -> Result<ExpressionResult, Thrown> {
         if self.arguments.len() != arguments.len() {
-            return Err(format!("Argument mismatch, function expected {} arguments, recieved {}", self.arguments.len(), arguments.len()));
+            return Err(format!("Argument mismatch, function expected {} arguments, recieved {}", self.arguments.len(), arguments.len()).into());
+        }
+        if parent_env.call_depth >= parent_env.max_call_depth {
+            return Err(InterpreterError {
+                kind: InterpreterErrorKind::RangeError("Maximum call stack size exceeded".into()),
+            }
+            .to_string()
+            .into());
         }
         let mut block_env = parent_env.create_child_env();
+        block_env.call_depth += 1;
+        for (identifier, value) in self.captured.iter() {
+            block_env.define_variable(identifier.clone(), value.clone());
+        }
+        block_env.this = match &self.lexical_this {
+            Some(lexical_this) => (**lexical_this).clone(),
+            None => this,
+        };
         // load arguments into block environment
         for (index, argument) in self.arguments.iter().enumerate() {
             match argument {
                 Expression::Identifier(identifier) => {
-                    let result = eval_expression(arguments[index].clone(), &mut block_env);
-                    if let Ok(val) = result {
-                        block_env.define_variable(identifier.to_string(), val)
-                    }
+                    block_env.define_variable(identifier.to_string(), arguments[index].clone())
                 },
-                _ => return Err("SyntaxError: Argument declaration should be of identifier type".to_string())
+                _ => return Err("SyntaxError: Argument declaration should be of identifier type".to_string().into())
             }
         }
         let result = self.block.execute_block(&mut block_env);
@@ -56,7 +131,7 @@ mod function_tests {
 
     #[test]
     fn it_should_throw_error_when_call_with_more_parameters() {
-        let argument = Expression::Identifier("x".into());
+        let argument = ExpressionResult::Number(1.0);
         let block = Block::new(vec![]);
         let function = Function::new(vec![], block);
         let mut env = Environment::new();
@@ -1,27 +1,24 @@
 #[cfg(test)]
 mod integration_tests {
-    use crate::interpreter::errors::{InterpreterError, InterpreterErrorKind};
+    use crate::interpreter::errors::{InterpreterError, InterpreterErrorKind, ParserError, ParserErrorKind, RunError, SyntaxErrorKind};
     use crate::interpreter::process_statements;
     use crate::lexer::tokenize;
     use crate::parser::{Parser, separate_out_statements_and_parser_errors};
     use crate::interpreter::interpreter::{eval_expression, eval_statement, eval_statements};
-    use crate::ast::{Expression, ExpressionResult, Statement};
+    use crate::ast::{Completion, Expression, ExpressionResult, PrefixOperator, Statement};
     use crate::environment::Environment;
+    use crate::interpreter::methods::{get_index, get_property};
+    use crate::{run, run_with_mode, with_interpreter_stack, Mode};
 
-    fn eval_statement_at_index(statements: &Vec<Statement>, env: &mut Environment, index: usize) {
-        let statement = match &statements[index] {
-            Statement::Let(identifier, expression) => {
-                                Statement::Let(identifier.to_string(), expression.clone())
-                            }
-            Statement::ExpressionStatement(expression) => {
-                                Statement::ExpressionStatement(expression.clone())
-                            },
-            Statement::FunctionDeclaration(_identifier, _arguments, _block) => todo!(),
-            Statement::ReturnStatement(_expression) => todo!(),
-            Statement::ConditionalStatement(_condition, _block, _next_conditional) => todo!(),
-            Statement::While(_statement) => todo!(),
-        };
-        eval_statement(statement, env);
+    fn eval_statement_at_index(statements: &[Statement], env: &mut Environment, index: usize) {
+        eval_statement(statements[index].clone(), env);
+    }
+
+    fn parser_errors(result: Result<ExpressionResult, RunError>) -> Vec<ParserError> {
+        match result {
+            Err(RunError::Parser(errors)) => errors,
+            other => panic!("expected parser errors, got {:?}", other),
+        }
     }
 
     #[test]
@@ -388,7 +385,7 @@ mod integration_tests {
             _ => Expression::NumberLiteral(-255.0),
         };
         let (statements, errors) = separate_out_statements_and_parser_errors(results);
-        eval_statements(statements, &mut env);
+        eval_statements(statements, &mut env).unwrap();
         assert_eq!(
             env.get_variable("x").unwrap_or(ExpressionResult::Number(-255.0)),
             ExpressionResult::Number(3.0)
@@ -414,7 +411,7 @@ mod integration_tests {
         };
             let (statements, _errors) = separate_out_statements_and_parser_errors(results);
 
-        eval_statements(statements, &mut env);
+        eval_statements(statements, &mut env).unwrap();
         let result = eval_expression(expression.clone(), &mut env).unwrap();
         assert_eq!(result, ExpressionResult::Boolean(false));
     }
@@ -432,7 +429,7 @@ mod integration_tests {
         };
                 let (statements, _errors) = separate_out_statements_and_parser_errors(results);
 
-        eval_statements(statements, &mut env);
+        eval_statements(statements, &mut env).unwrap();
         let result = eval_expression(expression.clone(), &mut env).unwrap();
         assert_eq!(result, ExpressionResult::Boolean(true));
     }
@@ -450,7 +447,7 @@ mod integration_tests {
         };
                 let (statements, _errors) = separate_out_statements_and_parser_errors(results);
 
-        eval_statements(statements, &mut env);
+        eval_statements(statements, &mut env).unwrap();
         let result = eval_expression(expression.clone(), &mut env).unwrap();
         assert_eq!(result, ExpressionResult::Boolean(false));
     }
@@ -490,7 +487,7 @@ mod integration_tests {
         let mut env = Environment::new();
         let (statements, _errors) = separate_out_statements_and_parser_errors(results);
 
-        eval_statements(statements, &mut env);
+        eval_statements(statements, &mut env).unwrap();
 
         let stored_value = env.get_variable("x").unwrap();
         assert_eq!(stored_value, ExpressionResult::Number(4.0));
@@ -524,7 +521,7 @@ mod integration_tests {
         };
                 let (statements, _errors) = separate_out_statements_and_parser_errors(results);
 
-        eval_statements(statements, &mut env);
+        eval_statements(statements, &mut env).unwrap();
         let result = eval_expression(expression, &mut env);
         assert_eq!(result.unwrap(), ExpressionResult::Boolean(true));
     }
@@ -542,7 +539,7 @@ mod integration_tests {
         };
                         let (statements, _errors) = separate_out_statements_and_parser_errors(results);
 
-        eval_statements(statements, &mut env);
+        eval_statements(statements, &mut env).unwrap();
         let stored_value = env.get_variable("x").unwrap();
         assert_eq!(stored_value, ExpressionResult::String("apple".to_string()));
         let stored_value = env.get_variable("y").unwrap();
@@ -572,7 +569,7 @@ mod integration_tests {
         };
         let (statements, _errors) = separate_out_statements_and_parser_errors(results);
 
-        eval_statements(statements, &mut env);
+        eval_statements(statements, &mut env).unwrap();
         let stored_value = env.get_variable("x").unwrap();
         assert_eq!(stored_value, ExpressionResult::String("apple".to_string()));
         let stored_value = env.get_variable("y").unwrap();
@@ -619,21 +616,21 @@ mod integration_tests {
         };
         let (statements, _errors) = separate_out_statements_and_parser_errors(results);
 
-        eval_statements(statements, &mut env);
+        eval_statements(statements, &mut env).unwrap();
         let stored_value = env.get_variable("x").unwrap();
         assert_eq!(stored_value, ExpressionResult::String("apple".to_string()));
         let stored_value = env.get_variable("y").unwrap();
         assert_eq!(stored_value, ExpressionResult::String("5".to_string()));
 
         let result = eval_expression(expression, &mut env);
-        assert!(result.is_err(), "NaN");
+        assert!(result.unwrap().is_nan());
         let result = eval_expression(second_expression, &mut env);
         assert_eq!(
             result.unwrap(),
             ExpressionResult::Number(5.0)
         );
         let result = eval_expression(third_expression, &mut env);
-        assert!(result.is_err(), "NaN");
+        assert!(result.unwrap().is_nan());
         let result = eval_expression(fourth_expression, &mut env);
         assert_eq!(
             result.unwrap(),
@@ -700,7 +697,7 @@ mod integration_tests {
         };
         let (statements, _errors) = separate_out_statements_and_parser_errors(results);
 
-        process_statements(statements.clone(), &mut env);
+        process_statements(statements.clone(), &mut env).unwrap();
         let result = eval_expression(expression, &mut env);
 
         assert_eq!(
@@ -730,7 +727,7 @@ mod integration_tests {
         };
         let (statements, _errors) = separate_out_statements_and_parser_errors(results);
 
-        process_statements(statements.clone(), &mut env);
+        process_statements(statements.clone(), &mut env).unwrap();
         let result = eval_expression(expression, &mut env);
 
         assert_eq!(
@@ -762,7 +759,7 @@ mod integration_tests {
             _ => Expression::NumberLiteral(-255.0),
         };
         let (statements, _errors) = separate_out_statements_and_parser_errors(results);
-        process_statements(statements.clone(), &mut env);
+        process_statements(statements.clone(), &mut env).unwrap();
         let x = env.get_variable("x".into());
 
         assert_eq!(
@@ -797,7 +794,7 @@ mod integration_tests {
         };
         let (statements, _errors) = separate_out_statements_and_parser_errors(results);
 
-        process_statements(statements.clone(), &mut env);
+        process_statements(statements.clone(), &mut env).unwrap();
         let result = eval_expression(expression, &mut env);
 
         assert_eq!(
@@ -827,7 +824,7 @@ mod integration_tests {
         let (statements, _errors) = separate_out_statements_and_parser_errors(results);
         assert_eq!(statements.len(), 2);
 
-        eval_statements(statements.clone(), &mut env);
+        eval_statements(statements.clone(), &mut env).unwrap();
         assert_eq!(
             env.get_variable("x".into()),
             Some(ExpressionResult::Number(7.0))
@@ -850,7 +847,7 @@ mod integration_tests {
         assert_eq!(results.len(), 2);
         let (statements, _errors) = separate_out_statements_and_parser_errors(results);
 
-        eval_statements(statements.clone(), &mut env);
+        eval_statements(statements.clone(), &mut env).unwrap();
         assert_eq!(
             env.get_variable("x".into()),
             Some(ExpressionResult::Number(3.0))
@@ -872,7 +869,7 @@ mod integration_tests {
         let results = parser.parse();
         let mut env = Environment::new();
         let (statements, _errors) = separate_out_statements_and_parser_errors(results);
-        eval_statements(statements.clone(), &mut env);
+        eval_statements(statements.clone(), &mut env).unwrap();
         assert_eq!(
             env.get_variable("x".into()),
             Some(ExpressionResult::Number(4.0))
@@ -902,7 +899,7 @@ mod integration_tests {
         let results = parser.parse();
         let mut env = Environment::new();
         let (statements, _errors) = separate_out_statements_and_parser_errors(results);
-        eval_statements(statements.clone(), &mut env);
+        eval_statements(statements.clone(), &mut env).unwrap();
         assert_eq!(
             env.get_variable("x".into()),
             Some(ExpressionResult::Number(-1.0))
@@ -933,7 +930,7 @@ mod integration_tests {
         let results = parser.parse();
         let mut env = Environment::new();
         let (statements, _errors) = separate_out_statements_and_parser_errors(results);
-        eval_statements(statements.clone(), &mut env);
+        eval_statements(statements.clone(), &mut env).unwrap();
         assert_eq!(
             env.get_variable("x".into()),
             Some(ExpressionResult::Number(3.0))
@@ -956,7 +953,7 @@ mod integration_tests {
         let results = parser.parse();
         let mut env = Environment::new();
         let (statements, _errors) = separate_out_statements_and_parser_errors(results);
-        eval_statements(statements.clone(), &mut env);
+        eval_statements(statements.clone(), &mut env).unwrap();
         assert_eq!(
             env.get_variable("x".into()),
             Some(ExpressionResult::Number(6.0))
@@ -974,7 +971,7 @@ mod integration_tests {
         let results = parser.parse();
         let mut env = Environment::new();
         let (statements, _errors) = separate_out_statements_and_parser_errors(results);
-        eval_statements(statements.clone(), &mut env);
+        eval_statements(statements.clone(), &mut env).unwrap();
         assert_eq!(
             env.get_variable("x".into()),
             Some(ExpressionResult::Number(2.0))
@@ -993,7 +990,7 @@ mod integration_tests {
         let results = parser.parse();
         let mut env = Environment::new();
         let (statements, _errors) = separate_out_statements_and_parser_errors(results);
-        eval_statements(statements.clone(), &mut env);
+        eval_statements(statements.clone(), &mut env).unwrap();
         assert_eq!(
             env.get_variable("x".into()),
             Some(ExpressionResult::Number(5.0))
@@ -1012,13 +1009,57 @@ mod integration_tests {
         let results = parser.parse();
         let mut env = Environment::new();
         let (statements, _errors) = separate_out_statements_and_parser_errors(results);
-        eval_statements(statements.clone(), &mut env);
+        eval_statements(statements.clone(), &mut env).unwrap();
         assert_eq!(
             env.get_variable("x".into()),
             Some(ExpressionResult::Number(-1.0))
         );
     }
 
+    #[test]
+    fn it_handles_percent_equals() {
+        let input = "
+            let x = 7;
+            x %= 3;
+        ";
+
+        let tokens = tokenize(input);
+        let mut parser = Parser::new(tokens);
+        let results = parser.parse();
+        let mut env = Environment::new();
+        let (statements, _errors) = separate_out_statements_and_parser_errors(results);
+        eval_statements(statements.clone(), &mut env).unwrap();
+        assert_eq!(
+            env.get_variable("x"),
+            Some(ExpressionResult::Number(1.0))
+        );
+    }
+
+    #[test]
+    fn it_handles_star_star_equals() {
+        let input = "
+            let x = 3;
+            x **= 3;
+            let y = 3;
+            y *= 2;
+        ";
+
+        let tokens = tokenize(input);
+        let mut parser = Parser::new(tokens);
+        let results = parser.parse();
+        let mut env = Environment::new();
+        let (statements, _errors) = separate_out_statements_and_parser_errors(results);
+        eval_statements(statements.clone(), &mut env).unwrap();
+        assert_eq!(
+            env.get_variable("x"),
+            Some(ExpressionResult::Number(27.0))
+        );
+        assert_eq!(
+            env.get_variable("y"),
+            Some(ExpressionResult::Number(6.0))
+        );
+    }
+
     #[test]
     fn it_handles_else() {
         let input = "
@@ -1035,7 +1076,7 @@ mod integration_tests {
         let results = parser.parse();
         let mut env = Environment::new();
         let (statements, _errors) = separate_out_statements_and_parser_errors(results);
-        eval_statements(statements.clone(), &mut env);
+        eval_statements(statements.clone(), &mut env).unwrap();
         assert_eq!(
             env.get_variable("x".into()),
             Some(ExpressionResult::Number(1.0))
@@ -1058,7 +1099,7 @@ mod integration_tests {
         let results = parser.parse();
         let mut env = Environment::new();
         let (statements, _errors) = separate_out_statements_and_parser_errors(results);
-        eval_statements(statements.clone(), &mut env);
+        eval_statements(statements.clone(), &mut env).unwrap();
         assert_eq!(
             env.get_variable("x".into()),
             Some(ExpressionResult::Number(1.0))
@@ -1083,7 +1124,7 @@ mod integration_tests {
         let results = parser.parse();
         let mut env = Environment::new();
         let (statements, _errors) = separate_out_statements_and_parser_errors(results);
-        eval_statements(statements.clone(), &mut env);
+        eval_statements(statements.clone(), &mut env).unwrap();
         assert_eq!(
             env.get_variable("x".into()),
             Some(ExpressionResult::Number(4.0))
@@ -1099,7 +1140,7 @@ mod integration_tests {
         let results = parser.parse();
         let mut env = Environment::new();
         let (statements, _errors) = separate_out_statements_and_parser_errors(results);
-        eval_statements(statements.clone(), &mut env);
+        eval_statements(statements.clone(), &mut env).unwrap();
         assert_eq!(
             env.get_variable("x".into()),
             Some(ExpressionResult::Number(2.0))
@@ -1115,7 +1156,7 @@ mod integration_tests {
         let results = parser.parse();
         let mut env = Environment::new();
         let (statements, _errors) = separate_out_statements_and_parser_errors(results);
-        eval_statements(statements.clone(), &mut env);
+        eval_statements(statements.clone(), &mut env).unwrap();
         assert_eq!(
             env.get_variable("x".into()),
             Some(ExpressionResult::Number(1.0))
@@ -1131,7 +1172,7 @@ mod integration_tests {
         let results = parser.parse();
         let mut env = Environment::new();
         let (statements, _errors) = separate_out_statements_and_parser_errors(results);
-        eval_statements(statements.clone(), &mut env);
+        eval_statements(statements.clone(), &mut env).unwrap();
         assert_eq!(
             env.get_variable("x".into()),
             Some(ExpressionResult::Number(2.0))
@@ -1147,7 +1188,7 @@ mod integration_tests {
         let results = parser.parse();
         let mut env = Environment::new();
         let (statements, _errors) = separate_out_statements_and_parser_errors(results);
-        eval_statements(statements.clone(), &mut env);
+        eval_statements(statements.clone(), &mut env).unwrap();
         assert_eq!(
             env.get_variable("x".into()),
             Some(ExpressionResult::Number(5.0))
@@ -1168,7 +1209,7 @@ mod integration_tests {
         let results = parser.parse();
         let mut env = Environment::new();
         let (statements, _errors) = separate_out_statements_and_parser_errors(results);
-        eval_statements(statements.clone(), &mut env);
+        eval_statements(statements.clone(), &mut env).unwrap();
         assert_eq!(
             env.get_variable("x".into()),
             Some(ExpressionResult::Number(5.0))
@@ -1216,7 +1257,7 @@ mod integration_tests {
             _ => Expression::NumberLiteral(-255.0),
         };
 
-        process_statements(statements, &mut env);
+        process_statements(statements, &mut env).unwrap();
 
         let expected_result = eval_expression(function_call, &mut env);
 
@@ -1230,4 +1271,2122 @@ mod integration_tests {
             0
         );
     }
+
+    #[test]
+    fn it_creates_an_array_with_range() {
+        let input = "range(0, 5, 2);";
+        let tokens = tokenize(input);
+        let mut parser = Parser::new(tokens);
+        let results = parser.parse();
+        let mut env = Environment::new();
+        let (statements, _errors) = separate_out_statements_and_parser_errors(results);
+        let range_call = match &statements[0] {
+            Statement::ExpressionStatement(expression) => expression.clone(),
+            _ => Expression::NumberLiteral(-255.0),
+        };
+
+        let result = eval_expression(range_call, &mut env);
+
+        assert_eq!(
+            result.unwrap(),
+            ExpressionResult::Array(vec![
+                ExpressionResult::Number(0.0),
+                ExpressionResult::Number(2.0),
+                ExpressionResult::Number(4.0),
+            ])
+        );
+    }
+
+    #[test]
+    fn it_iterates_a_range_with_for_of() {
+        let input = "
+            let sum = 0;
+            let count = 0;
+            for (let i of range(5, 0, -1)) {
+                sum += i;
+                ++count;
+            }
+        ";
+        let tokens = tokenize(input);
+        let mut parser = Parser::new(tokens);
+        let results = parser.parse();
+        let mut env = Environment::new();
+        let (statements, errors) = separate_out_statements_and_parser_errors(results);
+        assert_eq!(errors.len(), 0);
+
+        process_statements(statements, &mut env).unwrap();
+
+        assert_eq!(
+            env.get_variable("sum"),
+            Some(ExpressionResult::Number(15.0))
+        );
+        assert_eq!(
+            env.get_variable("count"),
+            Some(ExpressionResult::Number(5.0))
+        );
+        assert_eq!(
+            env.get_variable("i"),
+            None
+        );
+    }
+
+    #[test]
+    fn it_spreads_an_array_into_call_arguments() {
+        let input = "
+            function add(a, b, c) { return a + b + c; }
+            let numbers = [1, 2, 3];
+            add(...numbers);
+        ";
+        let tokens = tokenize(input);
+        let mut parser = Parser::new(tokens);
+        let results = parser.parse();
+        let mut env = Environment::new();
+        let (statements, errors) = separate_out_statements_and_parser_errors(results);
+        let function_call = match &statements[2] {
+            Statement::ExpressionStatement(expression) => expression.clone(),
+            _ => Expression::NumberLiteral(-255.0),
+        };
+        assert_eq!(errors.len(), 0);
+
+        process_statements(statements, &mut env).unwrap();
+
+        assert_eq!(
+            eval_expression(function_call, &mut env),
+            Ok(ExpressionResult::Number(6.0))
+        );
+    }
+
+    #[test]
+    fn it_preserves_argument_order_when_mixing_spread() {
+        let input = "
+            function digits(a, b, c, d) { return \"\" + a + b + c + d; }
+            let middle = [2, 3];
+            digits(1, ...middle, 4);
+        ";
+        let tokens = tokenize(input);
+        let mut parser = Parser::new(tokens);
+        let results = parser.parse();
+        let mut env = Environment::new();
+        let (statements, _errors) = separate_out_statements_and_parser_errors(results);
+        let function_call = match &statements[2] {
+            Statement::ExpressionStatement(expression) => expression.clone(),
+            _ => Expression::NumberLiteral(-255.0),
+        };
+
+        process_statements(statements, &mut env).unwrap();
+
+        assert_eq!(
+            eval_expression(function_call, &mut env),
+            Ok(ExpressionResult::String("1234".into()))
+        );
+    }
+
+    #[test]
+    fn it_throws_type_error_when_spreading_a_non_array() {
+        let input = "
+            function identity(a) { return a; }
+            identity(...5);
+        ";
+        let tokens = tokenize(input);
+        let mut parser = Parser::new(tokens);
+        let results = parser.parse();
+        let mut env = Environment::new();
+        let (statements, _errors) = separate_out_statements_and_parser_errors(results);
+        let function_call = match &statements[1] {
+            Statement::ExpressionStatement(expression) => expression.clone(),
+            _ => Expression::NumberLiteral(-255.0),
+        };
+
+        process_statements(statements, &mut env).unwrap_err();
+
+        assert_eq!(
+            eval_expression(function_call, &mut env),
+            Err(InterpreterError {
+                kind: InterpreterErrorKind::TypeError("5 is not iterable".into())
+            }
+            .to_string().into())
+        );
+    }
+
+    #[test]
+    fn it_counts_commas_with_split_length() {
+        let input = "
+            let csv = \"a,b,c,d\";
+            let commas = csv.split(\",\").length - 1;
+        ";
+        let tokens = tokenize(input);
+        let mut parser = Parser::new(tokens);
+        let results = parser.parse();
+        let mut env = Environment::new();
+        let (statements, errors) = separate_out_statements_and_parser_errors(results);
+        assert_eq!(errors.len(), 0);
+
+        process_statements(statements, &mut env).unwrap();
+
+        assert_eq!(
+            env.get_variable("commas"),
+            Some(ExpressionResult::Number(3.0))
+        );
+    }
+
+    #[test]
+    fn it_reads_string_length_and_characters() {
+        let input = "
+            let word = \"hello\";
+            let size = word.length;
+            let second = word[1];
+            let missing = word[-1];
+        ";
+        let tokens = tokenize(input);
+        let mut parser = Parser::new(tokens);
+        let results = parser.parse();
+        let mut env = Environment::new();
+        let (statements, errors) = separate_out_statements_and_parser_errors(results);
+        assert_eq!(errors.len(), 0);
+
+        process_statements(statements, &mut env).unwrap();
+
+        assert_eq!(
+            env.get_variable("size"),
+            Some(ExpressionResult::Number(5.0))
+        );
+        assert_eq!(
+            env.get_variable("second"),
+            Some(ExpressionResult::String("e".into()))
+        );
+        assert_eq!(
+            env.get_variable("missing"),
+            Some(ExpressionResult::Undefined)
+        );
+    }
+
+    #[test]
+    fn it_pushes_and_pops_a_let_bound_array() {
+        let input = "
+            let numbers = [1, 2];
+            let pushed_length = numbers.push(3);
+            let popped = numbers.pop();
+            let length = numbers.length;
+        ";
+        let tokens = tokenize(input);
+        let mut parser = Parser::new(tokens);
+        let results = parser.parse();
+        let mut env = Environment::new();
+        let (statements, errors) = separate_out_statements_and_parser_errors(results);
+        assert_eq!(errors.len(), 0);
+
+        process_statements(statements, &mut env).unwrap();
+
+        assert_eq!(
+            env.get_variable("pushed_length"),
+            Some(ExpressionResult::Number(3.0))
+        );
+        assert_eq!(
+            env.get_variable("popped"),
+            Some(ExpressionResult::Number(3.0))
+        );
+        assert_eq!(
+            env.get_variable("length"),
+            Some(ExpressionResult::Number(2.0))
+        );
+    }
+
+    #[test]
+    fn it_pushes_to_an_outer_array_from_a_loop() {
+        let input = "
+            let squares = [];
+            for (let i of range(1, 4)) {
+                squares.push(i * i);
+            }
+            let empty = [];
+            let nothing = empty.pop();
+        ";
+        let tokens = tokenize(input);
+        let mut parser = Parser::new(tokens);
+        let results = parser.parse();
+        let mut env = Environment::new();
+        let (statements, errors) = separate_out_statements_and_parser_errors(results);
+        assert_eq!(errors.len(), 0);
+
+        process_statements(statements, &mut env).unwrap();
+
+        assert_eq!(
+            env.get_variable("squares"),
+            Some(ExpressionResult::Array(vec![
+                ExpressionResult::Number(1.0),
+                ExpressionResult::Number(4.0),
+                ExpressionResult::Number(9.0),
+            ]))
+        );
+        assert_eq!(
+            env.get_variable("nothing"),
+            Some(ExpressionResult::Undefined)
+        );
+    }
+
+    #[test]
+    fn it_treats_empty_arrays_and_objects_as_truthy() {
+        let input = "
+            let array_branch = false;
+            let object_branch = false;
+            if ([]) { array_branch = true; }
+            if ({}) { object_branch = true; }
+        ";
+        let tokens = tokenize(input);
+        let mut parser = Parser::new(tokens);
+        let results = parser.parse();
+        let mut env = Environment::new();
+        let (statements, errors) = separate_out_statements_and_parser_errors(results);
+        assert_eq!(errors.len(), 0);
+
+        process_statements(statements, &mut env).unwrap();
+
+        assert_eq!(
+            env.get_variable("array_branch"),
+            Some(ExpressionResult::Boolean(true))
+        );
+        assert_eq!(
+            env.get_variable("object_branch"),
+            Some(ExpressionResult::Boolean(true))
+        );
+    }
+
+    #[test]
+    fn it_reads_object_properties() {
+        let input = "
+            let point = { x: 1, \"y\": 2 };
+            let sum = point.x + point[\"y\"];
+            let missing = point.z;
+        ";
+        let tokens = tokenize(input);
+        let mut parser = Parser::new(tokens);
+        let results = parser.parse();
+        let mut env = Environment::new();
+        let (statements, errors) = separate_out_statements_and_parser_errors(results);
+        assert_eq!(errors.len(), 0);
+
+        process_statements(statements, &mut env).unwrap();
+
+        assert_eq!(
+            env.get_variable("sum"),
+            Some(ExpressionResult::Number(3.0))
+        );
+        assert_eq!(
+            env.get_variable("missing"),
+            Some(ExpressionResult::Undefined)
+        );
+    }
+
+    #[test]
+    fn it_stringifies_values_to_json() {
+        let input = "
+            let numbers = JSON.stringify([1, 2, 3]);
+            let object = JSON.stringify({ a: 1, b: \"two\", c: null, d: [true] });
+        ";
+        let tokens = tokenize(input);
+        let mut parser = Parser::new(tokens);
+        let results = parser.parse();
+        let mut env = Environment::new();
+        let (statements, errors) = separate_out_statements_and_parser_errors(results);
+        assert_eq!(errors.len(), 0);
+
+        process_statements(statements, &mut env).unwrap();
+
+        assert_eq!(
+            env.get_variable("numbers"),
+            Some(ExpressionResult::String("[1,2,3]".into()))
+        );
+        assert_eq!(
+            env.get_variable("object"),
+            Some(ExpressionResult::String("{\"a\":1,\"b\":\"two\",\"c\":null,\"d\":[true]}".into()))
+        );
+    }
+
+    #[test]
+    fn it_lists_own_property_names_like_keys() {
+        let input = "
+            let point = { x: 1, y: 2, z: 3 };
+            let keys = Object.keys(point);
+            let names = Object.getOwnPropertyNames(point);
+        ";
+        let tokens = tokenize(input);
+        let mut parser = Parser::new(tokens);
+        let results = parser.parse();
+        let mut env = Environment::new();
+        let (statements, errors) = separate_out_statements_and_parser_errors(results);
+        assert_eq!(errors.len(), 0);
+
+        process_statements(statements, &mut env).unwrap();
+
+        let expected = ExpressionResult::Array(vec![
+            ExpressionResult::String("x".into()),
+            ExpressionResult::String("y".into()),
+            ExpressionResult::String("z".into()),
+        ]);
+        assert_eq!(env.get_variable("keys"), Some(expected.clone()));
+        assert_eq!(env.get_variable("names"), env.get_variable("keys"));
+    }
+
+    #[test]
+    fn it_runs_source_against_an_environment() {
+        let mut env = Environment::new();
+        run("let x = 2;", &mut env).unwrap();
+        let result = run("function double(n) { return n * 2; } let y = double(x);", &mut env);
+
+        assert_eq!(result.unwrap(), ExpressionResult::Undefined);
+        assert_eq!(env.get_variable("y"), Some(ExpressionResult::Number(4.0)));
+    }
+
+    #[test]
+    fn it_returns_parser_errors_without_running() {
+        let mut env = Environment::new();
+        let result = run("let x = 1; let = 2;", &mut env);
+
+        assert_eq!(parser_errors(result).len(), 1);
+        assert_eq!(env.get_variable("x"), None);
+    }
+
+    #[test]
+    fn it_destructures_nested_arrays() {
+        let input = "let [[a, b], [c]] = [[1, 2], [3]];";
+        let tokens = tokenize(input);
+        let mut parser = Parser::new(tokens);
+        let results = parser.parse();
+        let mut env = Environment::new();
+        let (statements, errors) = separate_out_statements_and_parser_errors(results);
+        assert_eq!(errors.len(), 0);
+
+        process_statements(statements, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("a"), Some(ExpressionResult::Number(1.0)));
+        assert_eq!(env.get_variable("b"), Some(ExpressionResult::Number(2.0)));
+        assert_eq!(env.get_variable("c"), Some(ExpressionResult::Number(3.0)));
+    }
+
+    #[test]
+    fn it_destructures_arrays_and_objects_nested_in_each_other() {
+        let input = "
+            let [[a], { b }] = [[1], { b: 2 }];
+            let { point: [x, y], missing } = { point: [3, 4] };
+        ";
+        let tokens = tokenize(input);
+        let mut parser = Parser::new(tokens);
+        let results = parser.parse();
+        let mut env = Environment::new();
+        let (statements, errors) = separate_out_statements_and_parser_errors(results);
+        assert_eq!(errors.len(), 0);
+
+        process_statements(statements, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("a"), Some(ExpressionResult::Number(1.0)));
+        assert_eq!(env.get_variable("b"), Some(ExpressionResult::Number(2.0)));
+        assert_eq!(env.get_variable("x"), Some(ExpressionResult::Number(3.0)));
+        assert_eq!(env.get_variable("y"), Some(ExpressionResult::Number(4.0)));
+        assert_eq!(env.get_variable("missing"), Some(ExpressionResult::Undefined));
+    }
+
+    #[test]
+    fn it_runs_a_program_spanning_many_lines_as_a_unit() {
+        let source = "
+            function add(a, b) {
+                return a + b;
+            }
+
+            let numbers = [
+                1,
+                2
+            ];
+            let total = add(
+                numbers[0],
+                numbers[1]
+            );
+        ";
+        let mut env = Environment::new();
+        run(source, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("total"), Some(ExpressionResult::Number(3.0)));
+    }
+
+    #[test]
+    fn it_returns_the_top_level_return_value_in_module_mode() {
+        let source = "
+            function answer() {
+                return 42;
+            }
+            let result = answer();
+            return result;
+        ";
+        let mut env = Environment::new();
+        let result = run_with_mode(source, &mut env, Mode::Module);
+
+        assert_eq!(result.unwrap(), ExpressionResult::Number(42.0));
+    }
+
+    #[test]
+    fn it_rejects_top_level_return_in_script_mode() {
+        let mut env = Environment::new();
+        let result = run("let x = 1; while (x < 2) { return 42; }", &mut env);
+
+        assert_eq!(
+            parser_errors(result),
+            vec![ParserError {
+                kind: ParserErrorKind::SyntaxError(Some(SyntaxErrorKind::IllegalReturnStatement))
+            }]
+        );
+        assert_eq!(env.get_variable("x"), None);
+    }
+
+    #[test]
+    fn it_coerces_string_operands_to_numbers_when_subtracting() {
+        let input = "let x = \"6\" - 2;";
+        let tokens = tokenize(input);
+        let mut parser = Parser::new(tokens);
+        let results = parser.parse();
+        let mut env = Environment::new();
+        let (statements, errors) = separate_out_statements_and_parser_errors(results);
+        assert_eq!(errors.len(), 0);
+
+        process_statements(statements, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("x"), Some(ExpressionResult::Number(4.0)));
+    }
+
+    #[test]
+    fn it_coerces_string_operands_to_numbers_when_multiplying_and_dividing() {
+        let input = "let product = \"6\" * \"2\"; let quotient = \"6\" / \"2\";";
+        let tokens = tokenize(input);
+        let mut parser = Parser::new(tokens);
+        let results = parser.parse();
+        let mut env = Environment::new();
+        let (statements, errors) = separate_out_statements_and_parser_errors(results);
+        assert_eq!(errors.len(), 0);
+
+        process_statements(statements, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("product"), Some(ExpressionResult::Number(12.0)));
+        assert_eq!(env.get_variable("quotient"), Some(ExpressionResult::Number(3.0)));
+    }
+
+    #[test]
+    fn it_gives_nan_for_arithmetic_on_a_non_numeric_string() {
+        let mut env = Environment::new();
+        let source = "
+            let product = \"abc\" * 2;
+            let difference = \"abc\" - 1;
+            let negated = -\"abc\";
+            let empty = \"\" - 1;
+            let padded = \" 5 \" * 1;
+        ";
+        run(source, &mut env).unwrap();
+
+        assert!(env.get_variable("product").unwrap().is_nan());
+        assert!(env.get_variable("difference").unwrap().is_nan());
+        assert!(env.get_variable("negated").unwrap().is_nan());
+        assert_eq!(env.get_variable("empty"), Some(ExpressionResult::Number(-1.0)));
+        assert_eq!(env.get_variable("padded"), Some(ExpressionResult::Number(5.0)));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn it_serializes_parser_output_to_json() {
+        let json = crate::parse_to_json("let x = 1 + 2;");
+        let statements: Vec<Result<Statement, String>> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(
+            statements,
+            vec![Ok(Statement::Let(
+                "x".into(),
+                Expression::Operation(
+                    Box::new(Expression::NumberLiteral(1.0)),
+                    crate::ast::Operator::Add,
+                    Box::new(Expression::NumberLiteral(2.0))
+                )
+            ))]
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn it_serializes_parser_errors_as_messages() {
+        let json = crate::parse_to_json("let = 1;");
+
+        assert!(json.contains("\"Err\""));
+    }
+
+    #[test]
+    fn it_loops_with_empty_for_sections_until_a_break() {
+        let input = "
+            let count = 0;
+            for (;;) {
+                count += 1;
+                if (count == 5) break;
+            }
+        ";
+        let tokens = tokenize(input);
+        let mut parser = Parser::new(tokens);
+        let results = parser.parse();
+        let mut env = Environment::new();
+        let (statements, errors) = separate_out_statements_and_parser_errors(results);
+        assert_eq!(errors.len(), 0);
+
+        process_statements(statements, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("count"), Some(ExpressionResult::Number(5.0)));
+    }
+
+    #[test]
+    fn it_runs_a_for_loop_with_every_section() {
+        let input = "
+            let sum = 0;
+            for (let i = 0; i < 4; ++i) {
+                sum += i;
+            }
+        ";
+        let tokens = tokenize(input);
+        let mut parser = Parser::new(tokens);
+        let results = parser.parse();
+        let mut env = Environment::new();
+        let (statements, errors) = separate_out_statements_and_parser_errors(results);
+        assert_eq!(errors.len(), 0);
+
+        process_statements(statements, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("sum"), Some(ExpressionResult::Number(6.0)));
+        // the loop variable only exists inside the loop
+        assert_eq!(env.get_variable("i"), None);
+    }
+
+    #[test]
+    fn it_breaks_out_of_while_and_for_of_loops() {
+        let input = "
+            let i = 0;
+            while (true) {
+                i += 1;
+                if (i > 2) { break; }
+            }
+            let last = 0;
+            for (let n of [1, 2, 3, 4]) {
+                if (n == 3) break;
+                last = n;
+            }
+        ";
+        let tokens = tokenize(input);
+        let mut parser = Parser::new(tokens);
+        let results = parser.parse();
+        let mut env = Environment::new();
+        let (statements, errors) = separate_out_statements_and_parser_errors(results);
+        assert_eq!(errors.len(), 0);
+
+        process_statements(statements, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("i"), Some(ExpressionResult::Number(3.0)));
+        assert_eq!(env.get_variable("last"), Some(ExpressionResult::Number(2.0)));
+    }
+
+    #[test]
+    fn it_treats_nan_as_not_equal_to_itself() {
+        let input = "
+            let x = 0 / 0;
+            let equal = x == x;
+            let not_equal = x != x;
+        ";
+        let tokens = tokenize(input);
+        let mut parser = Parser::new(tokens);
+        let results = parser.parse();
+        let mut env = Environment::new();
+        let (statements, errors) = separate_out_statements_and_parser_errors(results);
+        assert_eq!(errors.len(), 0);
+
+        process_statements(statements, &mut env).unwrap();
+
+        assert!(env.get_variable("x").unwrap().is_nan());
+        assert_eq!(env.get_variable("equal"), Some(ExpressionResult::Boolean(false)));
+        assert_eq!(env.get_variable("not_equal"), Some(ExpressionResult::Boolean(true)));
+    }
+
+    #[test]
+    fn it_distinguishes_null_checks_from_falsy_numbers() {
+        let input = "
+            let zero_is_null = 0 == null;
+            let zero_is_falsy = !0;
+        ";
+        let tokens = tokenize(input);
+        let mut parser = Parser::new(tokens);
+        let results = parser.parse();
+        let mut env = Environment::new();
+        let (statements, errors) = separate_out_statements_and_parser_errors(results);
+        assert_eq!(errors.len(), 0);
+
+        process_statements(statements, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("zero_is_null"), Some(ExpressionResult::Boolean(false)));
+        assert_eq!(env.get_variable("zero_is_falsy"), Some(ExpressionResult::Boolean(true)));
+    }
+
+    #[test]
+    fn it_distinguishes_null_checks_from_empty_strings() {
+        let input = "
+            let empty_is_null = \"\" == null;
+            let empty_is_falsy = !\"\";
+            let x = null;
+            let null_is_null = x == null;
+        ";
+        let tokens = tokenize(input);
+        let mut parser = Parser::new(tokens);
+        let results = parser.parse();
+        let mut env = Environment::new();
+        let (statements, errors) = separate_out_statements_and_parser_errors(results);
+        assert_eq!(errors.len(), 0);
+
+        process_statements(statements, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("empty_is_null"), Some(ExpressionResult::Boolean(false)));
+        assert_eq!(env.get_variable("empty_is_falsy"), Some(ExpressionResult::Boolean(true)));
+        assert_eq!(env.get_variable("null_is_null"), Some(ExpressionResult::Boolean(true)));
+    }
+
+    #[test]
+    fn it_only_assigns_with_logical_assignment_when_the_value_warrants_it() {
+        let input = "
+            let falsy = 0;
+            let truthy = 1;
+            let missing = null;
+            let kept = 0;
+            let zero = 0;
+            falsy ||= 5;
+            truthy ||= 5;
+            truthy &&= 7;
+            zero &&= 9;
+            missing ??= 3;
+            kept ??= 3;
+        ";
+        let tokens = tokenize(input);
+        let mut parser = Parser::new(tokens);
+        let results = parser.parse();
+        let mut env = Environment::new();
+        let (statements, errors) = separate_out_statements_and_parser_errors(results);
+        assert_eq!(errors.len(), 0);
+
+        process_statements(statements, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("falsy"), Some(ExpressionResult::Number(5.0)));
+        assert_eq!(env.get_variable("truthy"), Some(ExpressionResult::Number(7.0)));
+        assert_eq!(env.get_variable("zero"), Some(ExpressionResult::Number(0.0)));
+        assert_eq!(env.get_variable("missing"), Some(ExpressionResult::Number(3.0)));
+        assert_eq!(env.get_variable("kept"), Some(ExpressionResult::Number(0.0)));
+    }
+
+    #[test]
+    fn it_skips_the_right_side_of_logical_assignment_when_not_assigning() {
+        let input = "
+            let calls = 0;
+            function bump() {
+                calls = calls + 1;
+                return 10;
+            }
+            let a = 1;
+            let b = 0;
+            let c = 2;
+            a ||= bump();
+            b &&= bump();
+            c ??= bump();
+            let d = c ?? bump();
+        ";
+        let tokens = tokenize(input);
+        let mut parser = Parser::new(tokens);
+        let results = parser.parse();
+        let mut env = Environment::new();
+        let (statements, errors) = separate_out_statements_and_parser_errors(results);
+        assert_eq!(errors.len(), 0);
+
+        process_statements(statements, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("calls"), Some(ExpressionResult::Number(0.0)));
+        assert_eq!(env.get_variable("a"), Some(ExpressionResult::Number(1.0)));
+        assert_eq!(env.get_variable("b"), Some(ExpressionResult::Number(0.0)));
+        assert_eq!(env.get_variable("d"), Some(ExpressionResult::Number(2.0)));
+    }
+
+    #[test]
+    fn it_compares_strings_lexicographically() {
+        let input = "
+            let a_before_b = \"a\" < \"b\";
+            let b_before_a = \"b\" < \"a\";
+            let ten_before_nine = \"10\" < \"9\";
+            let ten_before_nine_numerically = \"10\" < 9;
+        ";
+        let tokens = tokenize(input);
+        let mut parser = Parser::new(tokens);
+        let results = parser.parse();
+        let mut env = Environment::new();
+        let (statements, errors) = separate_out_statements_and_parser_errors(results);
+        assert_eq!(errors.len(), 0);
+
+        process_statements(statements, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("a_before_b"), Some(ExpressionResult::Boolean(true)));
+        assert_eq!(env.get_variable("b_before_a"), Some(ExpressionResult::Boolean(false)));
+        assert_eq!(env.get_variable("ten_before_nine"), Some(ExpressionResult::Boolean(true)));
+        assert_eq!(env.get_variable("ten_before_nine_numerically"), Some(ExpressionResult::Boolean(false)));
+    }
+
+    #[test]
+    fn it_runs_a_leading_curly_brace_as_a_block() {
+        let input = "
+            let x = 1;
+            {}
+            {
+                x = 2;
+            }
+            let object = ({ x: 3 });
+        ";
+        let tokens = tokenize(input);
+        let mut parser = Parser::new(tokens);
+        let results = parser.parse();
+        let mut env = Environment::new();
+        let (statements, errors) = separate_out_statements_and_parser_errors(results);
+        assert_eq!(errors.len(), 0);
+
+        process_statements(statements, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("x"), Some(ExpressionResult::Number(2.0)));
+        assert_eq!(
+            env.get_variable("object").map(|object| get_property(&object, "x")),
+            Some(ExpressionResult::Number(3.0))
+        );
+    }
+
+    #[test]
+    fn it_reports_a_let_and_function_sharing_a_name_as_a_syntax_error() {
+        let mut env = Environment::new();
+        let result = run("let f = 1; function f() {}", &mut env);
+
+        let errors = parser_errors(result);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].to_string(),
+            "Uncaught SyntaxError: Identifier 'f' has already been declared"
+        );
+    }
+
+    #[test]
+    fn it_evaluates_the_left_side_of_less_than_or_equal_once() {
+        let mut env = Environment::new();
+        run("let x = 1; let r = x++ <= 1; let s = x-- >= 3;", &mut env).unwrap();
+
+        assert_eq!(env.get_variable("r"), Some(ExpressionResult::Boolean(true)));
+        assert_eq!(env.get_variable("s"), Some(ExpressionResult::Boolean(false)));
+        assert_eq!(env.get_variable("x"), Some(ExpressionResult::Number(1.0)));
+    }
+
+    #[test]
+    fn it_reads_the_last_element_with_at() {
+        let mut env = Environment::new();
+        run("let values = [1, 2, 3]; let last = values.at(-1); let letter = \"abc\".at(-1);", &mut env).unwrap();
+
+        assert_eq!(env.get_variable("last"), Some(ExpressionResult::Number(3.0)));
+        assert_eq!(env.get_variable("letter"), Some(ExpressionResult::String("c".to_string())));
+    }
+
+    // runs on the default test thread with the default call depth, as run evaluates on a thread of its own
+    #[test]
+    fn it_catches_unbounded_recursion_and_keeps_running() {
+        let mut env = Environment::new();
+        let source = "
+            function recurse(n) { if (n > -1) { while (true) { recurse(n + 1); } } }
+            let caught = \"\";
+            try { recurse(0); } catch (e) { caught = e; }
+            let after = 1;
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(
+            env.get_variable("caught"),
+            Some(ExpressionResult::String("RangeError: Maximum call stack size exceeded".to_string()))
+        );
+        assert_eq!(env.get_variable("after"), Some(ExpressionResult::Number(1.0)));
+    }
+
+    #[test]
+    fn it_discards_variables_declared_in_a_bare_block() {
+        let tokens = tokenize("{ let x = 1; } x;");
+        let mut parser = Parser::new(tokens);
+        let (statements, errors) = separate_out_statements_and_parser_errors(parser.parse());
+        assert_eq!(errors.len(), 0);
+        let mut env = Environment::new();
+
+        assert_eq!(eval_statement(statements[0].clone(), &mut env), None);
+        assert_eq!(
+            eval_statement(statements[1].clone(), &mut env),
+            Some(Completion::Throw(
+                InterpreterError { kind: InterpreterErrorKind::ReferenceError("x".into()) }.to_string().into()
+            ))
+        );
+    }
+
+    #[test]
+    fn it_converts_a_declared_function_to_its_source() {
+        let mut env = Environment::new();
+        run("function add(a, b) { return a + b; } let text = \"\" + add; let plus = add; let sum = plus(1, 2);", &mut env).unwrap();
+
+        assert_eq!(
+            env.get_variable("text"),
+            Some(ExpressionResult::String("function add(a, b) {\n    return a + b;\n}".to_string()))
+        );
+        assert_eq!(env.get_variable("sum"), Some(ExpressionResult::Number(3.0)));
+    }
+
+    #[test]
+    fn it_runs_statements_separated_only_by_newlines() {
+        let tokens = tokenize("let a = 1\nlet b = 2\na + b");
+        let mut parser = Parser::new(tokens);
+        let (mut statements, errors) = separate_out_statements_and_parser_errors(parser.parse());
+        assert_eq!(errors.len(), 0);
+        assert_eq!(statements.len(), 3);
+        let mut env = Environment::new();
+
+        let Some(Statement::ExpressionStatement(sum)) = statements.pop() else {
+            panic!("a + b should be an expression statement");
+        };
+        process_statements(statements, &mut env).unwrap();
+
+        assert_eq!(eval_expression(sum, &mut env), Ok(ExpressionResult::Number(3.0)));
+    }
+
+    #[test]
+    fn it_returns_from_inside_an_if_and_an_else() {
+        let mut env = Environment::new();
+        let source = "
+            function sign(x) { if (x > 0) { return 1; } else { return -1; } return 0; }
+            let positive = sign(5);
+            let negative = sign(-5);
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("positive"), Some(ExpressionResult::Number(1.0)));
+        assert_eq!(env.get_variable("negative"), Some(ExpressionResult::Number(-1.0)));
+    }
+
+    #[test]
+    fn it_returns_from_inside_a_while_loop() {
+        let mut env = Environment::new();
+        let source = "
+            function first_above(limit) { let i = 0; while (true) { i = i + 1; if (i > limit) { return i; } } }
+            let found = first_above(3);
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("found"), Some(ExpressionResult::Number(4.0)));
+    }
+
+    #[test]
+    fn it_recurses_through_a_return_inside_an_if() {
+        let mut env = Environment::new();
+        run("function factorial(n) { if (n < 2) { return 1; } return n * factorial(n - 1); } let result = factorial(5);", &mut env).unwrap();
+
+        assert_eq!(env.get_variable("result"), Some(ExpressionResult::Number(120.0)));
+    }
+
+    #[test]
+    fn it_reads_an_uninitialized_let_as_undefined() {
+        let mut env = Environment::new();
+        run("let x; let type = typeof x; let value = x;", &mut env).unwrap();
+
+        assert_eq!(env.get_variable("type"), Some(ExpressionResult::String("undefined".to_string())));
+        assert_eq!(env.get_variable("value"), Some(ExpressionResult::Undefined));
+    }
+
+    #[test]
+    fn it_allows_typeof_on_an_undeclared_name() {
+        let mut env = Environment::new();
+        assert_eq!(
+            eval_expression(
+                Expression::Prefix(PrefixOperator::TypeOf, Box::new(Expression::Identifier("undeclaredName".into()))),
+                &mut env
+            ),
+            Ok(ExpressionResult::String("undefined".to_string()))
+        );
+        assert_eq!(
+            eval_expression(Expression::Identifier("undeclaredName".into()), &mut env),
+            Err(InterpreterError { kind: InterpreterErrorKind::ReferenceError("undeclaredName".into()) }.to_string().into())
+        );
+    }
+
+    #[test]
+    fn it_evaluates_calls_in_array_and_object_literals_left_to_right() {
+        let mut env = Environment::new();
+        let source = "
+            let count = 0;
+            function next() { count = count + 1; return count; }
+            let values = [next(), next(), next()];
+            let object = { a: next(), b: next() };
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(
+            env.get_variable("values"),
+            Some(ExpressionResult::Array(vec![
+                ExpressionResult::Number(1.0),
+                ExpressionResult::Number(2.0),
+                ExpressionResult::Number(3.0),
+            ]))
+        );
+        let object = env.get_variable("object").unwrap();
+        assert_eq!(get_property(&object, "a"), ExpressionResult::Number(4.0));
+        assert_eq!(get_property(&object, "b"), ExpressionResult::Number(5.0));
+        assert_eq!(env.get_variable("count"), Some(ExpressionResult::Number(5.0)));
+    }
+
+    #[test]
+    fn it_returns_the_value_of_the_last_expression_statement() {
+        let mut env = Environment::new();
+        let result = run("3 + 4; let y = 1;", &mut env).unwrap();
+        assert_eq!(result, ExpressionResult::Number(7.0));
+
+        env.define_variable("_".to_string(), result);
+        let result = run("_ * 2;", &mut env).unwrap();
+        assert_eq!(result, ExpressionResult::Number(14.0));
+    }
+
+    #[test]
+    fn it_continues_a_while_loop_from_its_condition() {
+        let mut env = Environment::new();
+        let source = "
+            let i = 0;
+            let sum = 0;
+            while (i < 5) { i++; if (i % 2 == 0) continue; sum += i; }
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("sum"), Some(ExpressionResult::Number(9.0)));
+        assert_eq!(env.get_variable("i"), Some(ExpressionResult::Number(5.0)));
+    }
+
+    #[test]
+    fn it_runs_the_update_of_a_for_loop_after_continue() {
+        let mut env = Environment::new();
+        run("let sum = 0; for (let i = 0; i < 5; i++) { if (i == 2) { continue; } sum += i; }", &mut env).unwrap();
+
+        assert_eq!(env.get_variable("sum"), Some(ExpressionResult::Number(8.0)));
+    }
+
+    #[test]
+    fn it_keeps_a_let_in_an_else_block_out_of_the_parent_scope() {
+        let tokens = tokenize("let x = 1; if (x > 3) { let y = 1; } else if (x > 2) { let y = 2; } else { let y = 3; x = y; }");
+        let mut parser = Parser::new(tokens);
+        let (statements, errors) = separate_out_statements_and_parser_errors(parser.parse());
+        assert_eq!(errors.len(), 0);
+        let mut env = Environment::new();
+
+        process_statements(statements, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("x"), Some(ExpressionResult::Number(3.0)));
+        assert_eq!(env.get_variable("y"), None);
+    }
+
+    #[test]
+    fn it_throws_when_an_if_or_while_condition_errors() {
+        let tokens = tokenize("if (undefinedVar > 1) {} while (undefinedVar > 1) {}");
+        let mut parser = Parser::new(tokens);
+        let (statements, errors) = separate_out_statements_and_parser_errors(parser.parse());
+        assert_eq!(errors.len(), 0);
+        let mut env = Environment::new();
+        let reference_error = Some(Completion::Throw(
+            InterpreterError { kind: InterpreterErrorKind::ReferenceError("undefinedVar".into()) }.to_string().into()
+        ));
+
+        assert_eq!(eval_statement(statements[0].clone(), &mut env), reference_error);
+        assert_eq!(eval_statement(statements[1].clone(), &mut env), reference_error);
+    }
+
+    #[test]
+    fn it_reports_an_error_from_a_let_initializer_without_defining_the_variable() {
+        let tokens = tokenize("let x = undefinedVar;");
+        let mut parser = Parser::new(tokens);
+        let (statements, errors) = separate_out_statements_and_parser_errors(parser.parse());
+        assert_eq!(errors.len(), 0);
+        let mut env = Environment::new();
+
+        assert_eq!(
+            eval_statement(statements[0].clone(), &mut env),
+            Some(Completion::Throw(
+                InterpreterError { kind: InterpreterErrorKind::ReferenceError("undefinedVar".into()) }.to_string().into()
+            ))
+        );
+        assert_eq!(env.get_variable("x"), None);
+    }
+
+    #[test]
+    fn it_stacks_typeof_like_a_double_not() {
+        let mut env = Environment::new();
+        let result = run("typeof typeof 5;", &mut env).unwrap();
+
+        assert_eq!(result, ExpressionResult::String("string".to_string()));
+    }
+
+    #[test]
+    fn it_deletes_object_properties() {
+        let mut env = Environment::new();
+        let source = "
+            let object = { a: 1, b: 2, c: 3 };
+            let deleted = delete object.a;
+            let missing = delete object.z;
+            delete object[\"b\"];
+            let other = delete 5;
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("deleted"), Some(ExpressionResult::Boolean(true)));
+        assert_eq!(env.get_variable("missing"), Some(ExpressionResult::Boolean(true)));
+        assert_eq!(env.get_variable("other"), Some(ExpressionResult::Boolean(true)));
+        let object = env.get_variable("object").unwrap();
+        assert_eq!(get_property(&object, "a"), ExpressionResult::Undefined);
+        assert_eq!(get_property(&object, "b"), ExpressionResult::Undefined);
+        assert_eq!(get_property(&object, "c"), ExpressionResult::Number(3.0));
+    }
+
+    #[test]
+    fn it_deletes_nested_properties() {
+        let mut env = Environment::new();
+        let source = "
+            let o = { a: { b: 1, c: 2 }, list: [{ d: 3 }] };
+            let deleted = delete o.a.b;
+            delete o[\"list\"][0].d;
+            let keys = Object.keys(o.a);
+            let inner = Object.keys(o.list[0]);
+            let message = \"\";
+            try { delete o.missing.x; } catch (e) { message = e; }
+            let outer = Object.keys(o);
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("deleted"), Some(ExpressionResult::Boolean(true)));
+        assert_eq!(env.get_variable("keys"), Some(ExpressionResult::Array(vec![ExpressionResult::String("c".into())])));
+        assert_eq!(env.get_variable("inner"), Some(ExpressionResult::Array(vec![])));
+        assert_eq!(
+            env.get_variable("message"),
+            Some(ExpressionResult::String("TypeError: Cannot convert undefined or null to object".into()))
+        );
+        // a failed delete doesn't leave the missing property behind
+        let outer = vec![ExpressionResult::String("a".into()), ExpressionResult::String("list".into())];
+        assert_eq!(env.get_variable("outer"), Some(ExpressionResult::Array(outer)));
+    }
+
+    #[test]
+    fn it_rejects_json_nested_too_deeply() {
+        let mut env = Environment::new();
+        let source = "
+            let text = \"1\";
+            for (let i = 0; i < 1001; i++) {
+                text = \"[\" + text + \"]\";
+            }
+            let message = \"\";
+            try {
+                JSON.parse(text);
+            } catch (error) {
+                message = error;
+            }
+            let parsed = JSON.parse(JSON.stringify([[1], { a: true }]));
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(
+            env.get_variable("message"),
+            Some(ExpressionResult::String("SyntaxError: JSON is nested more than 1000 levels deep".into()))
+        );
+        let parsed = env.get_variable("parsed").unwrap();
+        assert_eq!(get_property(&parsed, "length"), ExpressionResult::Number(2.0));
+    }
+
+    #[test]
+    fn it_checks_keys_with_in() {
+        let mut env = Environment::new();
+        let source = "
+            let object = { a: 1, b: undefined };
+            let present = \"a\" in object;
+            let undefinedValue = \"b\" in object;
+            let absent = \"c\" in object && true;
+            let index = 1 in [5, 6];
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("present"), Some(ExpressionResult::Boolean(true)));
+        assert_eq!(env.get_variable("undefinedValue"), Some(ExpressionResult::Boolean(true)));
+        assert_eq!(env.get_variable("absent"), Some(ExpressionResult::Boolean(false)));
+        assert_eq!(env.get_variable("index"), Some(ExpressionResult::Boolean(true)));
+    }
+
+    #[test]
+    fn it_throws_a_type_error_for_in_on_a_non_object() {
+        let mut env = Environment::new();
+        let source = "
+            let message = \"\";
+            try {
+                \"a\" in 5;
+            } catch (error) {
+                message = error;
+            }
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(
+            env.get_variable("message"),
+            Some(ExpressionResult::String("TypeError: Cannot use 'in' operator to search for 'a' in 5".into()))
+        );
+    }
+
+    #[test]
+    fn it_catches_a_thrown_value() {
+        let mut env = Environment::new();
+        let source = "
+            function check(n) {
+                if (n < 0) {
+                    throw \"negative: \" + n;
+                }
+                return n;
+            }
+            let caught = \"\";
+            let after = false;
+            try {
+                check(-2);
+                after = true;
+            } catch (error) {
+                caught = error;
+            }
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("caught"), Some(ExpressionResult::String("negative: -2".into())));
+        assert_eq!(env.get_variable("after"), Some(ExpressionResult::Boolean(false)));
+    }
+
+    #[test]
+    fn it_stops_at_an_uncaught_throw() {
+        let mut env = Environment::new();
+        let result = run("let before = 1; throw \"stop\"; let after = 2;", &mut env);
+
+        assert_eq!(result, Err(RunError::Runtime("Uncaught stop".to_string())));
+
+        assert_eq!(env.get_variable("before"), Some(ExpressionResult::Number(1.0)));
+        assert_eq!(env.get_variable("after"), None);
+    }
+
+    #[test]
+    fn it_only_runs_the_assignment_in_the_taken_branch_of_a_ternary() {
+        let mut env = Environment::new();
+        let source = "
+            let a = 0;
+            let b = 0;
+            let cond = true;
+            cond ? (a = 1) : (b = 2);
+            let c = 0;
+            let d = 0;
+            !cond ? c = 3 : d = 4;
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("a"), Some(ExpressionResult::Number(1.0)));
+        assert_eq!(env.get_variable("b"), Some(ExpressionResult::Number(0.0)));
+        assert_eq!(env.get_variable("c"), Some(ExpressionResult::Number(0.0)));
+        assert_eq!(env.get_variable("d"), Some(ExpressionResult::Number(4.0)));
+    }
+
+    #[test]
+    fn it_evaluates_nested_ternaries() {
+        let mut env = Environment::new();
+        run("let n = 5; let size = n < 3 ? \"small\" : n < 10 ? \"medium\" : \"large\";", &mut env).unwrap();
+
+        assert_eq!(env.get_variable("size"), Some(ExpressionResult::String("medium".into())));
+    }
+
+    #[test]
+    fn it_compares_number_properties_with_equality() {
+        let mut env = Environment::new();
+        let source = "
+            let obj = { a: 5, b: 5, c: 6, x: 5 };
+            let literal = obj.x == 5;
+            let same = obj.a == obj.b;
+            let different = obj.a == obj.c;
+            let sum = obj.a + obj.b == obj.c + 4;
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("literal"), Some(ExpressionResult::Boolean(true)));
+        assert_eq!(env.get_variable("same"), Some(ExpressionResult::Boolean(true)));
+        assert_eq!(env.get_variable("different"), Some(ExpressionResult::Boolean(false)));
+        assert_eq!(env.get_variable("sum"), Some(ExpressionResult::Boolean(true)));
+    }
+
+    #[test]
+    fn it_compares_string_properties_and_indices_with_equality() {
+        let mut env = Environment::new();
+        let source = "
+            let arr = [\"a\", \"b\"];
+            let obj = { name: \"a\", other: \"b\" };
+            let index = arr[0] == \"a\";
+            let mixed = obj.name == arr[0];
+            let reversed = \"b\" == obj.name;
+            let unequal = obj.name != obj.other;
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("index"), Some(ExpressionResult::Boolean(true)));
+        assert_eq!(env.get_variable("mixed"), Some(ExpressionResult::Boolean(true)));
+        assert_eq!(env.get_variable("reversed"), Some(ExpressionResult::Boolean(false)));
+        assert_eq!(env.get_variable("unequal"), Some(ExpressionResult::Boolean(true)));
+    }
+
+    #[test]
+    fn it_runs_finally_whether_or_not_the_try_throws() {
+        let mut env = Environment::new();
+        let source = "
+            let caught = \"\";
+            let log = \"\";
+            try {
+                log = log + \"try,\";
+                throw \"oops\";
+                log = log + \"unreachable,\";
+            } catch (e) {
+                caught = e;
+                log = log + \"catch,\";
+            } finally {
+                log = log + \"finally\";
+            }
+            let quiet = \"\";
+            try {
+                quiet = quiet + \"try,\";
+            } catch (e) {
+                quiet = quiet + \"catch,\";
+            } finally {
+                quiet = quiet + \"finally\";
+            }
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("caught"), Some(ExpressionResult::String("oops".into())));
+        assert_eq!(env.get_variable("log"), Some(ExpressionResult::String("try,catch,finally".into())));
+        assert_eq!(env.get_variable("quiet"), Some(ExpressionResult::String("try,finally".into())));
+    }
+
+    #[test]
+    fn it_runs_finally_before_returning_or_rethrowing() {
+        let mut env = Environment::new();
+        let source = "
+            let cleaned = 0;
+            function early() {
+                try {
+                    return 1;
+                } finally {
+                    cleaned = cleaned + 1;
+                }
+            }
+            let value = early();
+            let caught = \"\";
+            try {
+                try {
+                    throw \"inner\";
+                } finally {
+                    cleaned = cleaned + 1;
+                }
+            } catch (e) {
+                caught = e;
+            }
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("value"), Some(ExpressionResult::Number(1.0)));
+        assert_eq!(env.get_variable("caught"), Some(ExpressionResult::String("inner".into())));
+        assert_eq!(env.get_variable("cleaned"), Some(ExpressionResult::Number(2.0)));
+    }
+
+    #[test]
+    fn it_compares_values_with_object_is() {
+        let mut env = Environment::new();
+        let source = "
+            let notANumber = Object.is(parseFloat(\"x\"), parseFloat(\"y\"));
+            let zeros = Object.is(0, -0);
+            let ones = Object.is(1, 1);
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("notANumber"), Some(ExpressionResult::Boolean(true)));
+        assert_eq!(env.get_variable("zeros"), Some(ExpressionResult::Boolean(false)));
+        assert_eq!(env.get_variable("ones"), Some(ExpressionResult::Boolean(true)));
+    }
+
+    #[test]
+    fn it_evaluates_chained_member_access_and_calls() {
+        let mut env = Environment::new();
+        let source = "
+            function double(n) { return n * 2; }
+            let config = { inner: { name: \"deep\", list: [\"ab\", \"cde\"] }, double: double };
+            let name = config.inner.name;
+            let length = config.inner.list[1].length;
+            let last = config.inner.list.at(-1).split(\"\").length;
+            let doubled = config.double(4);
+            let fromArray = [double][0](5);
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("name"), Some(ExpressionResult::String("deep".into())));
+        assert_eq!(env.get_variable("length"), Some(ExpressionResult::Number(3.0)));
+        assert_eq!(env.get_variable("last"), Some(ExpressionResult::Number(3.0)));
+        assert_eq!(env.get_variable("doubled"), Some(ExpressionResult::Number(8.0)));
+        assert_eq!(env.get_variable("fromArray"), Some(ExpressionResult::Number(10.0)));
+    }
+
+    #[test]
+    fn it_increments_and_decrements_members_and_indices() {
+        let mut env = Environment::new();
+        let source = "
+            let obj = { n: 1, inner: { list: [10, 20] } };
+            let arr = [1, 2, 3];
+            let prefixed = ++obj.n;
+            let postfixed = arr[1]--;
+            obj.inner.list[0]++;
+            let i = 0;
+            arr[i++]++;
+        ";
+        run(source, &mut env).unwrap();
+
+        let obj = env.get_variable("obj").unwrap();
+        assert_eq!(get_property(&obj, "n"), ExpressionResult::Number(2.0));
+        assert_eq!(env.get_variable("prefixed"), Some(ExpressionResult::Number(2.0)));
+        assert_eq!(env.get_variable("postfixed"), Some(ExpressionResult::Number(2.0)));
+        assert_eq!(
+            env.get_variable("arr"),
+            Some(ExpressionResult::Array(vec![
+                ExpressionResult::Number(2.0),
+                ExpressionResult::Number(1.0),
+                ExpressionResult::Number(3.0),
+            ]))
+        );
+        let list = get_property(&get_property(&obj, "inner"), "list");
+        assert_eq!(get_index(&list, &ExpressionResult::Number(0.0)), ExpressionResult::Number(11.0));
+        assert_eq!(env.get_variable("i"), Some(ExpressionResult::Number(1.0)));
+    }
+
+    #[test]
+    fn it_runs_a_program_with_thousands_of_statements() {
+        let mut env = Environment::new();
+        // loops and calls copy the environment, so they run before thousands of variables are declared
+        let mut source = String::from(
+            "let total = 0;\nlet counted = 0;\nfor (let i = 0; i < 5000; i++) { counted++; }\n",
+        );
+        for index in 1..=5000 {
+            source.push_str(&format!("let v{} = {};\ntotal = total + v{};\n", index, index, index));
+        }
+        run(&source, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("total"), Some(ExpressionResult::Number(12502500.0)));
+        assert_eq!(env.get_variable("v5000"), Some(ExpressionResult::Number(5000.0)));
+        assert_eq!(env.get_variable("counted"), Some(ExpressionResult::Number(5000.0)));
+    }
+
+    #[test]
+    fn it_destructures_the_value_a_function_returns() {
+        let mut env = Environment::new();
+        let source = "
+            let calls = 0;
+            function getPair() {
+                calls++;
+                return [\"left\", { right: 2 }];
+            }
+            let [a, { right }] = getPair();
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("a"), Some(ExpressionResult::String("left".into())));
+        assert_eq!(env.get_variable("right"), Some(ExpressionResult::Number(2.0)));
+        assert_eq!(env.get_variable("calls"), Some(ExpressionResult::Number(1.0)));
+    }
+
+    #[test]
+    fn it_switches_on_strings_with_fall_through() {
+        let mut env = Environment::new();
+        let source = "
+            function describe(name) {
+                let log = \"\";
+                switch (name) {
+                    case \"a\":
+                        log = log + \"a\";
+                    case \"b\":
+                        log = log + \"b\";
+                        break;
+                    case \"1\":
+                        log = log + \"string one\";
+                        break;
+                    default:
+                        log = log + \"default\";
+                }
+                return log;
+            }
+            let fromA = describe(\"a\");
+            let fromB = describe(\"b\");
+            let fromOther = describe(\"z\");
+            let fromNumber = describe(1);
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("fromA"), Some(ExpressionResult::String("ab".into())));
+        assert_eq!(env.get_variable("fromB"), Some(ExpressionResult::String("b".into())));
+        assert_eq!(env.get_variable("fromOther"), Some(ExpressionResult::String("default".into())));
+        // cases compare strictly, so the number 1 doesn't match the string \"1\"
+        assert_eq!(env.get_variable("fromNumber"), Some(ExpressionResult::String("default".into())));
+    }
+
+    #[test]
+    fn it_reports_an_unterminated_string_literal() {
+        let mut env = Environment::new();
+        let errors = parser_errors(run("let x = \"abc", &mut env));
+
+        assert_eq!(
+            errors.iter().map(|error| error.to_string()).collect::<Vec<String>>(),
+            vec!["Uncaught SyntaxError: Unterminated string literal"]
+        );
+        assert_eq!(env.get_variable("x"), None);
+    }
+
+    #[test]
+    fn it_increments_an_uninitialized_variable_to_nan() {
+        let mut env = Environment::new();
+        let result = run("let x; ++x;", &mut env).unwrap();
+
+        assert!(result.is_nan());
+        assert!(env.get_variable("x").unwrap().is_nan());
+    }
+
+    #[test]
+    fn it_reports_unexpected_characters() {
+        let mut env = Environment::new();
+        let errors = parser_errors(run("let a = @;\nlet b = 1 # 2;", &mut env));
+
+        assert_eq!(
+            errors.iter().map(|error| error.to_string()).collect::<Vec<String>>(),
+            vec![
+                "Uncaught SyntaxError: Unexpected character '@'",
+                "Uncaught SyntaxError: Unexpected character '#'"
+            ]
+        );
+    }
+
+    #[test]
+    fn it_does_not_pass_this_into_nested_plain_functions() {
+        let mut env = Environment::new();
+        let source = "
+            let obj = {
+                x: 5,
+                inner: { y: 7 },
+                get() { return this.x; },
+                deep() { return this.inner.y; },
+                nested() {
+                    function read() { return this.x; }
+                    function kind() { return typeof this; }
+                    return [read(), kind()];
+                }
+            };
+            let fromMethod = obj.get();
+            let fromChain = obj.deep();
+            let [fromNested, nestedType] = obj.nested();
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("fromMethod"), Some(ExpressionResult::Number(5.0)));
+        assert_eq!(env.get_variable("fromChain"), Some(ExpressionResult::Number(7.0)));
+        assert_eq!(env.get_variable("fromNested"), Some(ExpressionResult::Undefined));
+        assert_eq!(env.get_variable("nestedType"), Some(ExpressionResult::String("undefined".into())));
+    }
+
+    #[test]
+    fn it_allows_dollar_signs_and_digits_in_identifiers() {
+        let mut env = Environment::new();
+        let result = run("let $x = 1; let a2 = 2; let nan = 3; $x + a2 + nan;", &mut env).unwrap();
+
+        assert_eq!(result, ExpressionResult::Number(6.0));
+    }
+
+    #[test]
+    fn it_reports_identifiers_that_start_with_a_digit() {
+        let mut env = Environment::new();
+        let errors = parser_errors(run("let 2a = 1;\nlet b = 2a;", &mut env));
+
+        assert_eq!(
+            errors.iter().map(|error| error.to_string()).collect::<Vec<String>>(),
+            vec![
+                "Uncaught SyntaxError: Invalid or unexpected token '2a'",
+                "Uncaught SyntaxError: Invalid or unexpected token '2a'"
+            ]
+        );
+    }
+
+    #[test]
+    fn it_captures_this_in_arrow_functions_but_not_plain_functions() {
+        let mut env = Environment::new();
+        let source = "
+            let obj = {
+                x: 5,
+                get() { let f = () => this.x; return f(); },
+                plain() { function g() { return this; } return g(); }
+            };
+            let fromArrow = obj.get();
+            let fromPlain = obj.plain();
+            let add = (a, b) => a + b;
+            let sum = add(1, 2);
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("fromArrow"), Some(ExpressionResult::Number(5.0)));
+        assert_eq!(env.get_variable("fromPlain"), Some(ExpressionResult::Undefined));
+        assert_eq!(env.get_variable("sum"), Some(ExpressionResult::Number(3.0)));
+    }
+
+    #[test]
+    fn it_rejects_keywords_as_variable_names() {
+        let mut env = Environment::new();
+        let errors = parser_errors(run("let if = 1;\nlet typeof = 2;\nlet null = 3;", &mut env));
+
+        assert_eq!(errors.len(), 3);
+        assert!(errors.iter().all(|error| error.to_string().starts_with("Uncaught SyntaxError")));
+    }
+
+    #[test]
+    fn it_converts_numbers_to_strings_in_other_bases() {
+        let mut env = Environment::new();
+        let result = run("let n = 255; [(255).toString(16), n.toString(2), n.toString(8)];", &mut env).unwrap();
+
+        assert_eq!(
+            result,
+            ExpressionResult::Array(vec![
+                ExpressionResult::String("ff".into()),
+                ExpressionResult::String("11111111".into()),
+                ExpressionResult::String("377".into()),
+            ])
+        );
+    }
+
+    #[test]
+    fn it_rounds_with_fround_and_to_fixed() {
+        let mut env = Environment::new();
+        let source = "
+            let single = Math.fround(1.1);
+            let tenth = (0.1).toFixed(1);
+            let half = (2.5).toFixed(0);
+            let sum = 0.1 + 0.2;
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("single"), Some(ExpressionResult::Number(1.100000023841858)));
+        assert_eq!(env.get_variable("tenth"), Some(ExpressionResult::String("0.1".into())));
+        assert_eq!(env.get_variable("half"), Some(ExpressionResult::String("3".into())));
+        // numbers are f64 like in JS, so the usual floating point error shows
+        assert_eq!(env.get_variable("sum"), Some(ExpressionResult::Number(0.30000000000000004)));
+    }
+
+    #[test]
+    fn it_transforms_strings_without_changing_the_original() {
+        let mut env = Environment::new();
+        let source = "
+            let greeting = \"  Hi There  \";
+            let upper = greeting.toUpperCase();
+            let lower = greeting.toLowerCase();
+            let trimmed = greeting.trim();
+            let hasThere = greeting.includes(\"There\");
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("upper"), Some(ExpressionResult::String("  HI THERE  ".into())));
+        assert_eq!(env.get_variable("lower"), Some(ExpressionResult::String("  hi there  ".into())));
+        assert_eq!(env.get_variable("trimmed"), Some(ExpressionResult::String("Hi There".into())));
+        assert_eq!(env.get_variable("hasThere"), Some(ExpressionResult::Boolean(true)));
+        assert_eq!(env.get_variable("greeting"), Some(ExpressionResult::String("  Hi There  ".into())));
+    }
+
+    #[test]
+    fn it_concatenates_strings_with_booleans_null_and_undefined() {
+        let mut env = Environment::new();
+        let result = run("[\"x\" + true, \"x\" + false, \"x\" + null, \"x\" + undefined, null + \"x\"];", &mut env).unwrap();
+
+        assert_eq!(
+            result,
+            ExpressionResult::Array(vec![
+                ExpressionResult::String("xtrue".into()),
+                ExpressionResult::String("xfalse".into()),
+                ExpressionResult::String("xnull".into()),
+                ExpressionResult::String("xundefined".into()),
+                ExpressionResult::String("nullx".into()),
+            ])
+        );
+    }
+
+    #[test]
+    fn it_slices_strings() {
+        let mut env = Environment::new();
+        let result = run("let s = \"hello\"; [s.slice(1, 3), s.slice(-2), s.substring(4, 1)];", &mut env).unwrap();
+
+        assert_eq!(
+            result,
+            ExpressionResult::Array(vec![
+                ExpressionResult::String("el".into()),
+                ExpressionResult::String("lo".into()),
+                ExpressionResult::String("ell".into()),
+            ])
+        );
+    }
+
+    #[test]
+    fn it_breaks_out_of_a_loop_from_nested_if_blocks() {
+        let mut env = Environment::new();
+        let source = "
+            let c1 = true;
+            let c2 = false;
+            let count = 0;
+            while (true) {
+                count++;
+                if (count == 3) { c2 = true; }
+                if (c1) {
+                    if (c2) {
+                        break;
+                    }
+                }
+            }
+            let after = count;
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("after"), Some(ExpressionResult::Number(3.0)));
+    }
+
+    #[test]
+    fn it_maps_and_filters_arrays_with_arrow_functions() {
+        let mut env = Environment::new();
+        let source = "
+            let doubled = [1, 2, 3].map(x => x * 2);
+            let large = [1, 2, 3].filter(x => x > 1);
+            let indices = [5, 6].map((x, i) => i);
+        ";
+        run(source, &mut env).unwrap();
+
+        let numbers = |values: Vec<f64>| ExpressionResult::Array(values.into_iter().map(ExpressionResult::Number).collect());
+        assert_eq!(env.get_variable("doubled"), Some(numbers(vec![2.0, 4.0, 6.0])));
+        assert_eq!(env.get_variable("large"), Some(numbers(vec![2.0, 3.0])));
+        assert_eq!(env.get_variable("indices"), Some(numbers(vec![0.0, 1.0])));
+    }
+
+    #[test]
+    fn it_rejects_map_callbacks_that_are_not_functions() {
+        let mut env = Environment::new();
+        let source = "
+            let message = \"\";
+            try {
+                [1, 2].map(5);
+            } catch (error) {
+                message = error;
+            }
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(
+            env.get_variable("message"),
+            Some(ExpressionResult::String("TypeError: 5 is not a function".into()))
+        );
+    }
+
+    #[test]
+    fn it_calls_curried_functions_that_close_over_their_arguments() {
+        let mut env = Environment::new();
+        let source = "
+            function curry(a) { return function(b) { return function(c) { return a + b + c; }; }; }
+            let a = 100;
+            let sum = curry(1)(2)(3);
+            let add = x => y => x + y;
+            let addTwo = add(2);
+            let arrowSum = addTwo(5);
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("sum"), Some(ExpressionResult::Number(6.0)));
+        assert_eq!(env.get_variable("arrowSum"), Some(ExpressionResult::Number(7.0)));
+    }
+
+    #[test]
+    fn it_collects_primes_with_functions_loops_and_conditionals() {
+        let mut env = Environment::new();
+        let source = "
+            let primes = [];
+            for (let i = 0; i <= 30; i++) {
+                if (isPrime(i)) {
+                    primes.push(i);
+                }
+            }
+
+            function isPrime(n) {
+                if (n < 2) {
+                    return false;
+                }
+                let divisor = 2;
+                while (divisor * divisor <= n) {
+                    if (n % divisor == 0) {
+                        return false;
+                    } else {
+                        divisor++;
+                    }
+                }
+                return true;
+            }
+        ";
+        run(source, &mut env).unwrap();
+
+        let expected = vec![2.0, 3.0, 5.0, 7.0, 11.0, 13.0, 17.0, 19.0, 23.0, 29.0];
+        assert_eq!(
+            env.get_variable("primes"),
+            Some(ExpressionResult::Array(expected.into_iter().map(ExpressionResult::Number).collect()))
+        );
+    }
+
+    #[test]
+    fn it_passes_declared_functions_as_callbacks() {
+        let mut env = Environment::new();
+        let source = "
+            function double(x) { return x * 2; }
+            function apply(f, x) { return f(x); }
+            let result = apply(double, 5);
+            let mapped = [1, 2].map(double);
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("result"), Some(ExpressionResult::Number(10.0)));
+        assert_eq!(
+            env.get_variable("mapped"),
+            Some(ExpressionResult::Array(vec![ExpressionResult::Number(2.0), ExpressionResult::Number(4.0)]))
+        );
+    }
+
+    #[test]
+    fn it_joins_arrays_with_nested_arrays_and_null() {
+        let mut env = Environment::new();
+        let result = run("[[1, 2], [3], null].join(\"-\") + \"|\" + [null, undefined, 4].join();", &mut env).unwrap();
+
+        assert_eq!(result, ExpressionResult::String("1,2-3-|,,4".into()));
+    }
+
+    #[test]
+    fn it_reads_the_parameter_count_of_functions_from_length() {
+        let mut env = Environment::new();
+        let source = "
+            function add(a, b) { return a + b; }
+            let arity = add.length;
+            let arrowArity = (x => x).length;
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("arity"), Some(ExpressionResult::Number(2.0)));
+        assert_eq!(env.get_variable("arrowArity"), Some(ExpressionResult::Number(1.0)));
+    }
+
+    #[test]
+    fn it_stops_infinite_recursion_at_the_configured_depth() {
+        let mut env = Environment::new();
+        // a shallow limit fits in the test thread's default stack
+        env.max_call_depth = 50;
+        let source = "
+            let depth = 0;
+            function f() { depth++; return f(); }
+            let caught = \"\";
+            try { f(); } catch (e) { caught = e; }
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(
+            env.get_variable("caught"),
+            Some(ExpressionResult::String("RangeError: Maximum call stack size exceeded".to_string()))
+        );
+        assert_eq!(env.get_variable("depth"), Some(ExpressionResult::Number(50.0)));
+    }
+
+    #[test]
+    fn it_evaluates_comma_sequences_in_order_and_gives_the_last() {
+        let mut env = Environment::new();
+        let source = "
+            let x = (1, 2, 3);
+            let list = [(1, 2), 3];
+            let sum = 0;
+            let j = 10;
+            for (let i = 0; i < 3; i++, j--) {
+                sum = sum + j;
+            }
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("x"), Some(ExpressionResult::Number(3.0)));
+        assert_eq!(
+            env.get_variable("list"),
+            Some(ExpressionResult::Array(vec![ExpressionResult::Number(2.0), ExpressionResult::Number(3.0)]))
+        );
+        assert_eq!(env.get_variable("sum"), Some(ExpressionResult::Number(27.0)));
+    }
+
+    #[test]
+    fn it_requires_parentheses_around_a_negative_base() {
+        let mut env = Environment::new();
+        let result = run("let a = (-2) ** 2; let b = -(2 ** 2); [a, b];", &mut env).unwrap();
+        assert_eq!(
+            result,
+            ExpressionResult::Array(vec![ExpressionResult::Number(4.0), ExpressionResult::Number(-4.0)])
+        );
+
+        let errors = parser_errors(run("-2 ** 2;", &mut env));
+        assert_eq!(
+            errors[0].to_string(),
+            "Uncaught SyntaxError: Unary operator used immediately before exponentiation expression. Parenthesis must be used to disambiguate operator precedence"
+        );
+    }
+
+    #[test]
+    fn it_converts_values_with_boolean_and_number() {
+        let mut env = Environment::new();
+        let source = "
+            let n = Number(\"42\");
+            let notNumber = Number(\"abc\");
+            let falsy = Boolean(0);
+            let truthy = Boolean(\"x\");
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("n"), Some(ExpressionResult::Number(42.0)));
+        assert!(env.get_variable("notNumber").unwrap().coerce_to_number().unwrap().is_nan());
+        assert_eq!(env.get_variable("falsy"), Some(ExpressionResult::Boolean(false)));
+        assert_eq!(env.get_variable("truthy"), Some(ExpressionResult::Boolean(true)));
+    }
+
+    #[test]
+    fn it_converts_values_with_string() {
+        let mut env = Environment::new();
+        let result = run("String(42) + \"|\" + String(true) + \"|\" + String(null) + \"|\" + String([1, [2, 3]]);", &mut env).unwrap();
+
+        assert_eq!(result, ExpressionResult::String("42|true|null|1,2,3".into()));
+    }
+
+    #[test]
+    fn it_hoists_functions_to_the_top_of_their_block_only() {
+        let mut env = Environment::new();
+        let source = "
+            let inside = 0;
+            if (true) {
+                inside = foo();
+                function foo() { return 1; }
+            }
+            let outside = typeof foo;
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("inside"), Some(ExpressionResult::Number(1.0)));
+        assert_eq!(env.get_variable("outside"), Some(ExpressionResult::String("undefined".into())));
+        assert_eq!(env.get_function("foo"), None);
+    }
+
+    #[test]
+    fn it_switches_on_numbers_with_a_default_before_other_cases() {
+        let mut env = Environment::new();
+        let source = "
+            function classify(x) {
+                let log = [];
+                switch (x) {
+                    case 1:
+                        log.push(\"one\");
+                        break;
+                    default:
+                        log.push(\"default\");
+                    case 2:
+                        log.push(\"two\");
+                    case 3:
+                        log.push(\"three\");
+                        break;
+                }
+                return log.join(\",\");
+            }
+            let matched = classify(1);
+            let fallThrough = classify(2);
+            let fromDefault = classify(9);
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("matched"), Some(ExpressionResult::String("one".into())));
+        assert_eq!(env.get_variable("fallThrough"), Some(ExpressionResult::String("two,three".into())));
+        // nothing matched, so it starts at default and falls through the cases after it
+        assert_eq!(env.get_variable("fromDefault"), Some(ExpressionResult::String("default,two,three".into())));
+    }
+
+    #[test]
+    fn it_writes_very_large_and_small_numbers_like_js() {
+        let mut env = Environment::new();
+        let result = run("\"\" + 100000000000000000000 + \" \" + 0.0000001 + \" \" + 1e21;", &mut env).unwrap();
+
+        assert_eq!(result, ExpressionResult::String("100000000000000000000 1e-7 1e+21".into()));
+    }
+
+    #[test]
+    fn it_reads_the_nan_infinity_and_undefined_globals() {
+        let mut env = Environment::new();
+        let source = "
+            let x = NaN;
+            let nanDiffers = x != x;
+            let large = Infinity > 1e308;
+            let negative = -Infinity;
+            let missing = undefined;
+            NaN = 1;
+            let stillNaN = typeof NaN == \"number\" && NaN != NaN;
+            function shadow() { let Infinity = 1; return Infinity + 1; }
+            let shadowed = shadow();
+            undefined = 1;
+            let stillUndefined = typeof undefined;
+            function shadowUndefined() { let undefined = 5; return undefined; }
+            let shadowedUndefined = shadowUndefined();
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("nanDiffers"), Some(ExpressionResult::Boolean(true)));
+        assert_eq!(env.get_variable("large"), Some(ExpressionResult::Boolean(true)));
+        assert_eq!(env.get_variable("negative"), Some(ExpressionResult::Number(f64::NEG_INFINITY)));
+        assert_eq!(env.get_variable("missing"), Some(ExpressionResult::Undefined));
+        assert_eq!(env.get_variable("stillNaN"), Some(ExpressionResult::Boolean(true)));
+        assert_eq!(env.get_variable("shadowed"), Some(ExpressionResult::Number(2.0)));
+        assert_eq!(env.get_variable("stillUndefined"), Some(ExpressionResult::String("undefined".to_string())));
+        assert_eq!(env.get_variable("shadowedUndefined"), Some(ExpressionResult::Number(5.0)));
+        assert_eq!(env.get_variable("Infinity"), Some(ExpressionResult::Number(f64::INFINITY)));
+    }
+
+    #[test]
+    fn it_assigns_to_array_elements_and_object_properties() {
+        let mut env = Environment::new();
+        let source = "
+            let arr = [1, 2];
+            arr[0] = 5;
+            arr[4] = 9;
+            let obj = { inner: { count: 1 } };
+            obj.key = \"v\";
+            obj[\"other\"] = arr.length;
+            obj.inner.count = 2;
+            let chained = obj.last = 3;
+            let first = arr[0];
+            let hole = arr[2];
+            let length = arr.length;
+            let key = obj.key;
+            let other = obj.other;
+            let count = obj.inner.count;
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("first"), Some(ExpressionResult::Number(5.0)));
+        assert_eq!(env.get_variable("hole"), Some(ExpressionResult::Undefined));
+        assert_eq!(env.get_variable("length"), Some(ExpressionResult::Number(5.0)));
+        assert_eq!(env.get_variable("key"), Some(ExpressionResult::String("v".to_string())));
+        assert_eq!(env.get_variable("other"), Some(ExpressionResult::Number(5.0)));
+        assert_eq!(env.get_variable("count"), Some(ExpressionResult::Number(2.0)));
+        assert_eq!(env.get_variable("chained"), Some(ExpressionResult::Number(3.0)));
+    }
+
+    #[test]
+    fn it_applies_compound_assignment_to_properties_and_elements() {
+        let mut env = Environment::new();
+        let source = "
+            let arr = [1, 2, 3];
+            let i = 0;
+            arr[i++] += 10;
+            arr[2] **= 2;
+            let obj = { n: 3, inner: { s: \"a\" } };
+            obj.n *= 2;
+            obj.inner.s += \"b\";
+            let missing = null;
+            missing ??= 4;
+            arr[i++] ||= 7;
+            let result = arr.join(\",\");
+            let n = obj.n;
+            let s = obj.inner.s;
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("result"), Some(ExpressionResult::String("11,2,9".to_string())));
+        assert_eq!(env.get_variable("i"), Some(ExpressionResult::Number(2.0)));
+        assert_eq!(env.get_variable("n"), Some(ExpressionResult::Number(6.0)));
+        assert_eq!(env.get_variable("s"), Some(ExpressionResult::String("ab".to_string())));
+        assert_eq!(env.get_variable("missing"), Some(ExpressionResult::Number(4.0)));
+    }
+
+    #[test]
+    fn it_checks_for_nan_and_finite_numbers() {
+        let mut env = Environment::new();
+        let source = "
+            let checks = [isNaN(\"abc\"), isNaN(5), isNaN(\"5\"), isNaN(0 / 0), isFinite(1 / 0), isFinite(-1 / 0), isFinite(\"12\"), isFinite(NaN)];
+        ";
+        run(source, &mut env).unwrap();
+
+        let expected = [true, false, false, true, false, false, true, false].map(ExpressionResult::Boolean).to_vec();
+        assert_eq!(env.get_variable("checks"), Some(ExpressionResult::Array(expected)));
+    }
+
+    #[test]
+    fn it_reads_postfix_increments_followed_by_an_addition_or_subtraction() {
+        let mut env = Environment::new();
+        let source = "
+            let x = 1;
+            let y = x++ + 1;
+            let a = 1;
+            let b = a++ - 1;
+            let c = 2;
+            let d = c + +c;
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("x"), Some(ExpressionResult::Number(2.0)));
+        assert_eq!(env.get_variable("y"), Some(ExpressionResult::Number(2.0)));
+        assert_eq!(env.get_variable("a"), Some(ExpressionResult::Number(2.0)));
+        assert_eq!(env.get_variable("b"), Some(ExpressionResult::Number(0.0)));
+        assert_eq!(env.get_variable("d"), Some(ExpressionResult::Number(4.0)));
+    }
+
+    #[test]
+    fn it_catches_thrown_numbers_and_objects_unchanged() {
+        let mut env = Environment::new();
+        let source = "
+            let kind;
+            let doubled;
+            try { throw 5; } catch (e) { kind = typeof e; doubled = e * 2; }
+            function fail() { throw { code: 1 }; }
+            let code;
+            try { fail(); } catch (e) { code = e.code; }
+        ";
+        run(source, &mut env).unwrap();
+
+        assert_eq!(env.get_variable("kind"), Some(ExpressionResult::String("number".to_string())));
+        assert_eq!(env.get_variable("doubled"), Some(ExpressionResult::Number(10.0)));
+        assert_eq!(env.get_variable("code"), Some(ExpressionResult::Number(1.0)));
+    }
+
+    #[test]
+    fn it_allows_keywords_as_property_names() {
+        let mut env = Environment::new();
+        let source = "
+            let o = {default: 1, delete: 2, for: 3, catch() { return 4; }};
+            o.default = 5;
+            let {delete: removed} = o;
+            let values = [o.default, removed, o.for, o.catch()];
+        ";
+        run(source, &mut env).unwrap();
+
+        let expected = [5.0, 2.0, 3.0, 4.0].map(ExpressionResult::Number).to_vec();
+        assert_eq!(env.get_variable("values"), Some(ExpressionResult::Array(expected)));
+    }
+
+    #[test]
+    fn it_shares_one_interpreter_thread_between_runs() {
+        let (depth, value) = with_interpreter_stack(|| {
+            let mut env = Environment::new();
+            run("let depth = 0; function f() { depth++; return f(); } try { f(); } catch (e) {}", &mut env).unwrap();
+            let value = run("depth + 1;", &mut env).unwrap();
+            (env.get_variable("depth"), value)
+        });
+
+        assert_eq!(depth, Some(ExpressionResult::Number(1000.0)));
+        assert_eq!(value, ExpressionResult::Number(1001.0));
+    }
 }
@@ -1,12 +1,15 @@
 use std::fmt::{Debug, Display};
 
-use crate::lexer::Token;
+use crate::{ast::ExpressionResult, lexer::Token};
 
 pub enum InterpreterErrorKind {
     ReferenceError(String),
     SyntaxError(Option<SyntaxErrorKind>),
     NaN,
-    DivisionByZero
+    RangeError(String),
+    TypeError(String),
+    // a value the script threw itself
+    Thrown(ExpressionResult)
 }
 
 #[derive(PartialEq)]
@@ -18,8 +21,18 @@ pub enum ParserErrorKind {
 pub enum SyntaxErrorKind {
     LeftSideAssignmentMustBeIdentifier,
     InvalidLeftSidePrefix,
+    InvalidLeftSidePostfix,
+    InvalidAssignmentTarget,
     UnexpectedToken(Token),
-    UnexpectedIdentifier(String)
+    UnexpectedIdentifier(String),
+    IllegalReturnStatement,
+    IdentifierAlreadyDeclared(String),
+    InvalidJson(String),
+    UnterminatedStringLiteral,
+    UnexpectedCharacter(String),
+    InvalidOrUnexpectedToken(String),
+    UnaryBeforeExponentiation,
+    AssignmentInCondition,
 }
 
 impl SyntaxErrorKind {
@@ -31,12 +44,30 @@ impl SyntaxErrorKind {
             Self::InvalidLeftSidePrefix => {
                 "Invalid left-hand side expression in prefix operation".to_string()
             }
+            Self::InvalidAssignmentTarget => "Invalid left-hand side in assignment".to_string(),
+            Self::InvalidLeftSidePostfix => {
+                "Invalid left-hand side expression in postfix operation".to_string()
+            }
             Self::UnexpectedToken(token) => {
                 format!("Unexpected token '{:#?}'", token)
             }
             Self::UnexpectedIdentifier(identifier) => {
                 format!("Unexpected identifier '{}'", identifier)
             }
+            Self::IllegalReturnStatement => "Illegal return statement".to_string(),
+            Self::IdentifierAlreadyDeclared(identifier) => {
+                format!("Identifier '{}' has already been declared", identifier)
+            }
+            Self::InvalidJson(message) => message.clone(),
+            Self::UnaryBeforeExponentiation => {
+                "Unary operator used immediately before exponentiation expression. Parenthesis must be used to disambiguate operator precedence".to_string()
+            }
+            Self::AssignmentInCondition => {
+                "Assignment used as a condition. Use == to compare, or wrap the assignment in a second pair of parentheses if it is intended".to_string()
+            }
+            Self::UnterminatedStringLiteral => "Unterminated string literal".to_string(),
+            Self::UnexpectedCharacter(character) => format!("Unexpected character '{}'", character),
+            Self::InvalidOrUnexpectedToken(text) => format!("Invalid or unexpected token '{}'", text),
         }
     }
 }
@@ -66,8 +97,14 @@ impl InterpreterError {
             InterpreterErrorKind::NaN => {
                 "NaN".to_string()
             },
-            InterpreterErrorKind::DivisionByZero => {
-                "Infinity".to_string()
+            InterpreterErrorKind::RangeError(message) => {
+                format!("Uncaught RangeError: {}", message)
+            },
+            InterpreterErrorKind::TypeError(message) => {
+                format!("Uncaught TypeError: {}", message)
+            },
+            InterpreterErrorKind::Thrown(value) => {
+                format!("Uncaught {}", value)
             }
         }
     }
@@ -107,4 +144,32 @@ impl Debug for ParserError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.to_string())
     }
+}
+
+/// Why `run` stopped: either the source didn't parse, in which case nothing ran,
+/// or a runtime error nothing caught ended it, written the way it should be shown
+#[derive(Debug, PartialEq)]
+pub enum RunError {
+    Parser(Vec<ParserError>),
+    Runtime(String),
+}
+
+/// Source the lexer couldn't read, with the line and column it starts at, both counted from 1
+#[derive(PartialEq)]
+pub struct LexError {
+    pub kind: SyntaxErrorKind,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Display for LexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Uncaught SyntaxError: {} ({}:{})", self.kind, self.line, self.column)
+    }
+}
+
+impl Debug for LexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
 }
\ No newline at end of file
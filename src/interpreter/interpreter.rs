@@ -1,46 +1,62 @@
-use crate::ast::{Expression, ExpressionResult, Statement, Node};
+use crate::ast::{Completion, Expression, ExpressionResult, Statement, Node, Thrown};
 use crate::environment::Environment;
 use crate::function::Function;
 use crate::interpreter::visitor::Evaluator;
 
-pub fn process_statements(
+// Evaluates on the current thread, unlike `run`, so it is only for the crate, where `run` has already
+// made sure there is room on the stack for the default call depth
+pub(crate) fn process_statements(
     mut statements: Vec<Statement>,
     env: &mut Environment,
-) -> ExpressionResult {
+) -> Result<ExpressionResult, String> {
     hoist(&mut statements, env);
     eval_statements(statements, env)
 }
 
-pub fn eval_statements(statements: Vec<Statement>, env: &mut Environment) -> ExpressionResult {
+// Returns the value given to a top level return, or otherwise the value of the last expression statement,
+// which is how the REPL knows what `_` should hold.  An error nothing caught stops running and is returned,
+// already written the way it should be shown, like `Uncaught ReferenceError: x is not defined`
+pub fn eval_statements(statements: Vec<Statement>, env: &mut Environment) -> Result<ExpressionResult, String> {
+    env.last_value = ExpressionResult::Undefined;
+    match run_statements(&statements, env) {
+        Some(Completion::Return(value)) => Ok(value),
+        Some(Completion::Throw(thrown)) => Err(thrown.uncaught_message()),
+        _ => Ok(env.last_value.clone()),
+    }
+}
+
+// Runs statements in order until one of them returns, breaks or throws.
+// Function declarations are skipped, as they were already declared when the statements were hoisted
+pub fn run_statements(statements: &[Statement], env: &mut Environment) -> Option<Completion> {
     for statement in statements {
-        let result = eval_statement(statement, env);
-        if let Some(value) = &result {
-            return value.clone();
+        let mut evaluator = Evaluator::new(env);
+        let completion = statement.accept(&mut evaluator);
+        if completion.is_some() {
+            return completion;
         }
     }
-    ExpressionResult::Undefined
+    None
 }
 
 // Function declarations should be parsed
 // If I wanted to support declaring variables with var, I'd also need to hoist those to match how var works
 pub fn hoist(statements: &mut Vec<Statement>, env: &mut Environment) {
-    for statement in &mut *statements {
-        match statement {
-            Statement::FunctionDeclaration(identifier, arguments, block) => {
-                let function = Function::new(arguments.clone(), block.clone());
-                env.set_function(identifier.clone(), function);
-            }
-            _ => {}
+    declare_functions(statements, env);
+    statements.retain(|statement| !matches!(statement, Statement::FunctionDeclaration(_, _, _)));
+}
+
+// Declares every function in the statements without removing the declarations, for statements that are
+// borrowed rather than owned, like the body of a block that runs many times
+pub fn declare_functions(statements: &[Statement], env: &mut Environment) {
+    for statement in statements {
+        if let Statement::FunctionDeclaration(identifier, arguments, block) = statement {
+            let function = Function::new(arguments.clone(), block.clone());
+            env.set_function(identifier.clone(), function);
         }
     }
-    *statements = statements
-        .iter()
-        .filter(|statement| !matches!(statement, Statement::FunctionDeclaration(_, _, _)))
-        .map(|statement| statement.clone())
-        .collect::<Vec<Statement>>();
 }
 
-pub fn eval_statement(statement: Statement, env: &mut Environment) -> Option<ExpressionResult> {
+pub fn eval_statement(statement: Statement, env: &mut Environment) -> Option<Completion> {
     let mut evaluator = Evaluator::new(env);
     statement.accept(&mut evaluator)
 }
@@ -48,7 +64,7 @@ pub fn eval_statement(statement: Statement, env: &mut Environment) -> Option<Exp
 pub fn eval_expression(
     expression: Expression,
     env: &mut Environment,
-) -> Result<ExpressionResult, String> {
+) -> Result<ExpressionResult, Thrown> {
     let mut evaluator = Evaluator::new(env);
     expression.accept(&mut evaluator)
 }
@@ -56,7 +72,7 @@ pub fn eval_expression(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::ast::{Operator, PrefixOperator};
+    use crate::ast::{Block, Operator, PrefixOperator};
 
     #[test]
     fn eval_expression_should_do_math() {
@@ -107,4 +123,37 @@ mod tests {
             env.get_variable("x").unwrap()
         );
     }
+
+    #[test]
+    fn eval_expression_should_evaluate_strings() {
+        let mut env = Environment::new();
+        let joined = Expression::Operation(
+            Box::new(Expression::String("a".into())),
+            Operator::Add,
+            Box::new(Expression::String("b".into())),
+        );
+        assert_eq!(
+            eval_expression(Expression::String("hi".into()), &mut env),
+            Ok(ExpressionResult::String("hi".into()))
+        );
+        assert_eq!(eval_expression(joined, &mut env), Ok(ExpressionResult::String("ab".into())));
+    }
+
+    #[test]
+    fn hoist_should_remove_function_declarations_in_place() {
+        let mut statements = vec![
+            Statement::ExpressionStatement(Expression::NumberLiteral(1.0)),
+            Statement::FunctionDeclaration("f".into(), vec![], Block::new(vec![])),
+            Statement::ExpressionStatement(Expression::NumberLiteral(2.0)),
+        ];
+        let allocation = statements.as_ptr();
+        let mut env = Environment::new();
+
+        hoist(&mut statements, &mut env);
+
+        assert!(env.has_function("f".into()));
+        assert_eq!(statements.len(), 2);
+        // the statements were kept where they were rather than copied into a new vector
+        assert_eq!(statements.as_ptr(), allocation);
+    }
 }
@@ -0,0 +1,425 @@
+use crate::ast::ExpressionResult;
+use crate::interpreter::errors::{InterpreterError, InterpreterErrorKind, SyntaxErrorKind};
+use crate::object::Object;
+
+/// How deeply JSON.parse lets arrays and objects nest before giving up, so hostile input can't exhaust the stack
+pub const DEFAULT_MAX_DEPTH: usize = 1000;
+
+/// JSON.stringify(value) converts a value to its JSON text.
+/// Values JSON can't represent (undefined and functions) produce undefined at the top level,
+/// are skipped inside objects, and become null inside arrays, matching JS.
+pub fn stringify(arguments: Vec<ExpressionResult>) -> Result<ExpressionResult, String> {
+    let value = arguments
+        .into_iter()
+        .next()
+        .unwrap_or(ExpressionResult::Undefined);
+    Ok(match stringify_value(&value) {
+        Some(json) => ExpressionResult::String(json),
+        None => ExpressionResult::Undefined,
+    })
+}
+
+fn stringify_value(value: &ExpressionResult) -> Option<String> {
+    match value {
+        ExpressionResult::Number(number) => {
+            if number.is_finite() {
+                Some(value.coerce_to_string())
+            } else {
+                Some("null".to_string())
+            }
+        }
+        ExpressionResult::String(string) => Some(quote(string)),
+        ExpressionResult::Boolean(_) | ExpressionResult::Null => Some(value.coerce_to_string()),
+        ExpressionResult::Array(elements) => {
+            let elements: Vec<String> = elements
+                .iter()
+                .map(|element| stringify_value(element).unwrap_or("null".to_string()))
+                .collect();
+            Some(format!("[{}]", elements.join(",")))
+        }
+        ExpressionResult::Object(object) => {
+            let properties: Vec<String> = object
+                .entries()
+                .filter_map(|(key, value)| {
+                    stringify_value(value).map(|json| format!("{}:{}", quote(key), json))
+                })
+                .collect();
+            Some(format!("{{{}}}", properties.join(",")))
+        }
+        ExpressionResult::NativeFunction(_)
+        | ExpressionResult::Function(_, _)
+        | ExpressionResult::Undefined => None,
+    }
+}
+
+/// JSON.parse(text) reads JSON text into a value, throwing a SyntaxError for anything that isn't valid JSON
+pub fn parse(arguments: Vec<ExpressionResult>) -> Result<ExpressionResult, String> {
+    let text = arguments
+        .first()
+        .unwrap_or(&ExpressionResult::Undefined)
+        .coerce_to_string();
+    parse_with_max_depth(&text, DEFAULT_MAX_DEPTH)
+}
+
+/// Parses JSON text like JSON.parse, but with a chosen limit on how deeply arrays and objects may nest
+pub fn parse_with_max_depth(text: &str, max_depth: usize) -> Result<ExpressionResult, String> {
+    let mut reader = JsonReader {
+        characters: text.chars().collect(),
+        position: 0,
+        max_depth,
+    };
+    let value = reader.read_value(0)?;
+    reader.skip_whitespace();
+    if reader.peek().is_some() {
+        return Err(reader.unexpected());
+    }
+    Ok(value)
+}
+
+fn json_error(message: String) -> String {
+    InterpreterError {
+        kind: InterpreterErrorKind::SyntaxError(Some(SyntaxErrorKind::InvalidJson(message))),
+    }
+    .to_string()
+}
+
+struct JsonReader {
+    characters: Vec<char>,
+    position: usize,
+    max_depth: usize,
+}
+
+impl JsonReader {
+    fn peek(&self) -> Option<char> {
+        self.characters.get(self.position).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.position += 1;
+        }
+    }
+
+    fn unexpected(&self) -> String {
+        match self.peek() {
+            Some(character) => json_error(format!(
+                "Unexpected token '{}' in JSON at position {}",
+                character, self.position
+            )),
+            None => json_error("Unexpected end of JSON input".to_string()),
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        if self.peek() != Some(expected) {
+            return Err(self.unexpected());
+        }
+        self.position += 1;
+        Ok(())
+    }
+
+    fn read_value(&mut self, depth: usize) -> Result<ExpressionResult, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('[') => self.read_array(depth + 1),
+            Some('{') => self.read_object(depth + 1),
+            Some('"') => Ok(ExpressionResult::String(self.read_string()?)),
+            Some('t') => self.read_literal("true", ExpressionResult::Boolean(true)),
+            Some('f') => self.read_literal("false", ExpressionResult::Boolean(false)),
+            Some('n') => self.read_literal("null", ExpressionResult::Null),
+            Some('-' | '0'..='9') => self.read_number(),
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    fn check_depth(&self, depth: usize) -> Result<(), String> {
+        if depth > self.max_depth {
+            return Err(json_error(format!(
+                "JSON is nested more than {} levels deep",
+                self.max_depth
+            )));
+        }
+        Ok(())
+    }
+
+    fn read_array(&mut self, depth: usize) -> Result<ExpressionResult, String> {
+        self.check_depth(depth)?;
+        self.expect('[')?;
+        let mut elements = vec![];
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.position += 1;
+            return Ok(ExpressionResult::Array(elements));
+        }
+        loop {
+            elements.push(self.read_value(depth)?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.position += 1,
+                Some(']') => {
+                    self.position += 1;
+                    return Ok(ExpressionResult::Array(elements));
+                }
+                _ => return Err(self.unexpected()),
+            }
+        }
+    }
+
+    fn read_object(&mut self, depth: usize) -> Result<ExpressionResult, String> {
+        self.check_depth(depth)?;
+        self.expect('{')?;
+        let mut object = Object::new();
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.position += 1;
+            return Ok(ExpressionResult::Object(object));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.read_string()?;
+            self.skip_whitespace();
+            self.expect(':')?;
+            object.set(key, self.read_value(depth)?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.position += 1,
+                Some('}') => {
+                    self.position += 1;
+                    return Ok(ExpressionResult::Object(object));
+                }
+                _ => return Err(self.unexpected()),
+            }
+        }
+    }
+
+    fn read_literal(&mut self, word: &str, value: ExpressionResult) -> Result<ExpressionResult, String> {
+        for expected in word.chars() {
+            self.expect(expected)?;
+        }
+        Ok(value)
+    }
+
+    // JSON numbers are stricter than JS ones: no leading zeros, no leading dot and no trailing dot
+    fn read_number(&mut self) -> Result<ExpressionResult, String> {
+        let start = self.position;
+        if self.peek() == Some('-') {
+            self.position += 1;
+        }
+        match self.peek() {
+            Some('0') => self.position += 1,
+            Some('1'..='9') => self.skip_digits(),
+            _ => return Err(self.unexpected()),
+        }
+        if self.peek() == Some('.') {
+            self.position += 1;
+            self.require_digits()?;
+        }
+        if matches!(self.peek(), Some('e' | 'E')) {
+            self.position += 1;
+            if matches!(self.peek(), Some('+' | '-')) {
+                self.position += 1;
+            }
+            self.require_digits()?;
+        }
+        let number: String = self.characters[start..self.position].iter().collect();
+        Ok(ExpressionResult::Number(number.parse().unwrap()))
+    }
+
+    fn skip_digits(&mut self) {
+        while matches!(self.peek(), Some('0'..='9')) {
+            self.position += 1;
+        }
+    }
+
+    fn require_digits(&mut self) -> Result<(), String> {
+        if !matches!(self.peek(), Some('0'..='9')) {
+            return Err(self.unexpected());
+        }
+        self.skip_digits();
+        Ok(())
+    }
+
+    fn read_string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut string = String::new();
+        loop {
+            match self.peek() {
+                Some('"') => {
+                    self.position += 1;
+                    return Ok(string);
+                }
+                Some('\\') => {
+                    self.position += 1;
+                    let escaped = match self.peek() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{08}',
+                        Some('f') => '\u{0c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => {
+                            self.position += 1;
+                            string.push(self.read_unicode_escape()?);
+                            continue;
+                        }
+                        _ => return Err(self.unexpected()),
+                    };
+                    self.position += 1;
+                    string.push(escaped);
+                }
+                // control characters have to be escaped inside JSON strings
+                Some(character) if (character as u32) < 0x20 => return Err(self.unexpected()),
+                Some(character) => {
+                    self.position += 1;
+                    string.push(character);
+                }
+                None => return Err(self.unexpected()),
+            }
+        }
+    }
+
+    // Reads the four hex digits after \u, joining a surrogate pair written as two escapes into one character
+    fn read_unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.read_hex_digits()?;
+        if (0xD800..0xDC00).contains(&high)
+            && self.characters.get(self.position..self.position + 2) == Some(&['\\', 'u'])
+        {
+            let position = self.position;
+            self.position += 2;
+            let low = self.read_hex_digits()?;
+            if (0xDC00..0xE000).contains(&low) {
+                let code = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+                return Ok(char::from_u32(code).unwrap());
+            }
+            self.position = position;
+        }
+        // a lone surrogate can't be held in a Rust string
+        Ok(char::from_u32(high).unwrap_or(char::REPLACEMENT_CHARACTER))
+    }
+
+    fn read_hex_digits(&mut self) -> Result<u32, String> {
+        let mut code = 0;
+        for _ in 0..4 {
+            match self.peek().and_then(|character| character.to_digit(16)) {
+                Some(digit) => {
+                    code = code * 16 + digit;
+                    self.position += 1;
+                }
+                None => return Err(self.unexpected()),
+            }
+        }
+        Ok(code)
+    }
+}
+
+pub fn quote(string: &str) -> String {
+    let mut quoted = String::from("\"");
+    for character in string.chars() {
+        match character {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            '\u{08}' => quoted.push_str("\\b"),
+            '\u{0c}' => quoted.push_str("\\f"),
+            control if control.is_control() && (control as u32) < 0x20 => {
+                quoted.push_str(&format!("\\u{:04x}", control as u32))
+            }
+            _ => quoted.push(character),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object::Object;
+
+    fn stringified(value: ExpressionResult) -> ExpressionResult {
+        stringify(vec![value]).unwrap()
+    }
+
+    #[test]
+    fn it_should_stringify_primitives() {
+        assert_eq!(stringified(ExpressionResult::Number(1.5)), ExpressionResult::String("1.5".into()));
+        assert_eq!(stringified(ExpressionResult::Boolean(true)), ExpressionResult::String("true".into()));
+        assert_eq!(stringified(ExpressionResult::Null), ExpressionResult::String("null".into()));
+        assert_eq!(stringified(ExpressionResult::Undefined), ExpressionResult::Undefined);
+    }
+
+    #[test]
+    fn it_should_quote_and_escape_strings() {
+        assert_eq!(
+            stringified(ExpressionResult::String("say \"hi\"\n".into())),
+            ExpressionResult::String("\"say \\\"hi\\\"\\n\"".into())
+        );
+    }
+
+    #[test]
+    fn it_should_stringify_arrays() {
+        let array = ExpressionResult::Array(vec![
+            ExpressionResult::Number(1.0),
+            ExpressionResult::Number(2.0),
+            ExpressionResult::Undefined,
+        ]);
+        assert_eq!(stringified(array), ExpressionResult::String("[1,2,null]".into()));
+    }
+
+    #[test]
+    fn it_should_stringify_objects_and_omit_undefined() {
+        let mut object = Object::new();
+        object.set("a".into(), ExpressionResult::Number(1.0));
+        object.set("skipped".into(), ExpressionResult::Undefined);
+        object.set("b".into(), ExpressionResult::Array(vec![]));
+        assert_eq!(
+            stringified(ExpressionResult::Object(object)),
+            ExpressionResult::String("{\"a\":1,\"b\":[]}".into())
+        );
+    }
+
+    #[test]
+    fn it_should_parse_nested_values() {
+        let result = parse(vec![ExpressionResult::String(
+            "{ \"a\": [1, -2.5e1, true, null], \"b\": \"x\\n\\u0041\\ud83d\\ude00\" }".into(),
+        )]);
+        let mut object = Object::new();
+        object.set(
+            "a".into(),
+            ExpressionResult::Array(vec![
+                ExpressionResult::Number(1.0),
+                ExpressionResult::Number(-25.0),
+                ExpressionResult::Boolean(true),
+                ExpressionResult::Null,
+            ]),
+        );
+        object.set("b".into(), ExpressionResult::String("x\nA\u{1F600}".into()));
+        assert_eq!(result, Ok(ExpressionResult::Object(object)));
+    }
+
+    #[test]
+    fn it_should_reject_invalid_json() {
+        let unexpected = |text: &str| parse(vec![ExpressionResult::String(text.into())]).unwrap_err();
+        assert_eq!(unexpected("[1,]"), "Uncaught SyntaxError: Unexpected token ']' in JSON at position 3");
+        assert_eq!(unexpected("01"), "Uncaught SyntaxError: Unexpected token '1' in JSON at position 1");
+        assert_eq!(unexpected("{\"a\": 1"), "Uncaught SyntaxError: Unexpected end of JSON input");
+    }
+
+    #[test]
+    fn it_should_stop_parsing_past_the_maximum_depth() {
+        let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        assert!(parse_with_max_depth(&nested(3), 3).is_ok());
+        assert_eq!(
+            parse_with_max_depth(&nested(4), 3),
+            Err("Uncaught SyntaxError: JSON is nested more than 3 levels deep".to_string())
+        );
+        assert_eq!(
+            parse(vec![ExpressionResult::String(nested(DEFAULT_MAX_DEPTH + 1))]),
+            Err("Uncaught SyntaxError: JSON is nested more than 1000 levels deep".to_string())
+        );
+    }
+}
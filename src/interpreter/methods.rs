@@ -0,0 +1,705 @@
+use crate::ast::{join_elements, number_to_string, string_to_number, ExpressionResult};
+use crate::interpreter::errors::{InterpreterError, InterpreterErrorKind};
+
+/// Reads a built in property from a value, such as `array.length`.
+/// Unknown properties are `undefined`, as they are in JS.
+pub fn get_property(value: &ExpressionResult, property: &str) -> ExpressionResult {
+    match (value, property) {
+        (ExpressionResult::Array(elements), "length") => {
+            ExpressionResult::Number(elements.len() as f64)
+        }
+        // count characters rather than bytes so multi-byte characters have a length of one
+        (ExpressionResult::String(string), "length") => {
+            ExpressionResult::Number(string.chars().count() as f64)
+        }
+        // a function's length is how many parameters it declares.  There are no default or rest
+        // parameters yet, which JS leaves out of the count
+        (ExpressionResult::Function(_, function), "length") => {
+            ExpressionResult::Number(function.parameter_count() as f64)
+        }
+        (ExpressionResult::Object(object), _) => object
+            .get(property)
+            .cloned()
+            .unwrap_or(ExpressionResult::Undefined),
+        _ => ExpressionResult::Undefined,
+    }
+}
+
+/// Reads `value[index]`.  Numeric indices read elements of arrays and characters of strings,
+/// anything else is treated as a property name, so `"abc"["length"]` is 3.
+pub fn get_index(value: &ExpressionResult, index: &ExpressionResult) -> ExpressionResult {
+    if let (Some(number), ExpressionResult::Array(_) | ExpressionResult::String(_)) = (numeric_index(index), value) {
+        if number.fract() != 0.0 || number < 0.0 {
+            return ExpressionResult::Undefined;
+        }
+        let position = number as usize;
+        return match value {
+            ExpressionResult::Array(elements) => elements
+                .get(position)
+                .cloned()
+                .unwrap_or(ExpressionResult::Undefined),
+            ExpressionResult::String(string) => string
+                .chars()
+                .nth(position)
+                .map(|character| ExpressionResult::String(character.to_string()))
+                .unwrap_or(ExpressionResult::Undefined),
+            _ => ExpressionResult::Undefined,
+        };
+    }
+    get_property(value, &index.coerce_to_string())
+}
+
+// A number, or a string holding a number written the way JS writes it, so "1" is an index like 1 is
+// while "01" and "1.0" stay property names
+fn numeric_index(index: &ExpressionResult) -> Option<f64> {
+    match index {
+        ExpressionResult::Number(number) => Some(*number),
+        ExpressionResult::String(string) => {
+            let number = string_to_number(string);
+            (number_to_string(number) == *string).then_some(number)
+        }
+        _ => None,
+    }
+}
+
+/// Writes `value[index] = new_value`.  Whole number indices write elements of arrays, filling any gap
+/// with undefined, and anything else sets a property of an object.  Like sloppy mode JS, writing to
+/// any other value does nothing, except for null and undefined which have no properties to set.
+pub fn set_index(
+    value: &mut ExpressionResult,
+    index: &ExpressionResult,
+    new_value: ExpressionResult,
+) -> Result<(), String> {
+    match value {
+        ExpressionResult::Array(elements) => {
+            if let Some(number) = numeric_index(index) {
+                if number.fract() == 0.0 && number >= 0.0 {
+                    let position = number as usize;
+                    if position >= elements.len() {
+                        elements.resize(position + 1, ExpressionResult::Undefined);
+                    }
+                    elements[position] = new_value;
+                }
+            }
+        }
+        ExpressionResult::Object(object) => object.set(index.coerce_to_string(), new_value),
+        ExpressionResult::Null | ExpressionResult::Undefined => {
+            return Err(InterpreterError {
+                kind: InterpreterErrorKind::TypeError(format!(
+                    "Cannot set properties of {} (setting '{}')",
+                    value, index
+                )),
+            }
+            .to_string())
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Whether calling this method changes the value it is called on.
+/// Values are not references, so the caller has to store the changed value back where it came from.
+pub fn is_mutating_method(value: &ExpressionResult, method: &str) -> bool {
+    matches!((value, method), (ExpressionResult::Array(_), "push" | "pop"))
+}
+
+/// Calls a built in method on a value, such as `string.split(",")`.
+pub fn call_method(
+    value: &mut ExpressionResult,
+    method: &str,
+    arguments: Vec<ExpressionResult>,
+) -> Result<ExpressionResult, String> {
+    match value {
+        ExpressionResult::String(string) => call_string_method(string, method, arguments),
+        ExpressionResult::Array(elements) => call_array_method(elements, method, arguments),
+        ExpressionResult::Number(number) => call_number_method(*number, method, arguments),
+        // objects such as JSON hold their methods as native function properties
+        ExpressionResult::Object(object) => match object.get(method) {
+            Some(ExpressionResult::NativeFunction(function)) => function.call(arguments),
+            _ => Err(not_a_function(method)),
+        },
+        _ => Err(not_a_function(method)),
+    }
+}
+
+fn call_array_method(
+    elements: &mut Vec<ExpressionResult>,
+    method: &str,
+    arguments: Vec<ExpressionResult>,
+) -> Result<ExpressionResult, String> {
+    match method {
+        "push" => {
+            elements.extend(arguments);
+            Ok(ExpressionResult::Number(elements.len() as f64))
+        }
+        "pop" => Ok(elements.pop().unwrap_or(ExpressionResult::Undefined)),
+        "join" => {
+            let separator = match arguments.first() {
+                None | Some(ExpressionResult::Undefined) => ",".to_string(),
+                Some(separator) => separator.coerce_to_string(),
+            };
+            Ok(ExpressionResult::String(join_elements(elements, &separator)))
+        }
+        "at" => Ok(relative_index(elements.len(), arguments.first())
+            .and_then(|position| elements.get(position).cloned())
+            .unwrap_or(ExpressionResult::Undefined)),
+        _ => Err(not_a_function(method)),
+    }
+}
+
+fn call_string_method(
+    string: &str,
+    method: &str,
+    arguments: Vec<ExpressionResult>,
+) -> Result<ExpressionResult, String> {
+    match method {
+        "split" => {
+            let parts: Vec<ExpressionResult> = match arguments.first() {
+                None | Some(ExpressionResult::Undefined) => {
+                    vec![ExpressionResult::String(string.to_string())]
+                }
+                Some(separator) => {
+                    let separator = separator.coerce_to_string();
+                    if separator.is_empty() {
+                        string
+                            .chars()
+                            .map(|character| ExpressionResult::String(character.to_string()))
+                            .collect()
+                    } else {
+                        string
+                            .split(separator.as_str())
+                            .map(|part| ExpressionResult::String(part.to_string()))
+                            .collect()
+                    }
+                }
+            };
+            Ok(ExpressionResult::Array(parts))
+        }
+        "at" => Ok(relative_index(string.chars().count(), arguments.first())
+            .and_then(|position| string.chars().nth(position))
+            .map(|character| ExpressionResult::String(character.to_string()))
+            .unwrap_or(ExpressionResult::Undefined)),
+        "slice" => {
+            let length = string.chars().count();
+            let start = relative_position(length, arguments.first(), 0);
+            let end = relative_position(length, arguments.get(1), length);
+            Ok(ExpressionResult::String(substring(string, start, end)))
+        }
+        // unlike slice, negative positions count as 0 and the positions are swapped when start is after end
+        "substring" => {
+            let length = string.chars().count();
+            let start = clamped_position(length, arguments.first(), 0);
+            let end = clamped_position(length, arguments.get(1), length);
+            Ok(ExpressionResult::String(substring(string, start.min(end), start.max(end))))
+        }
+        "toUpperCase" => Ok(ExpressionResult::String(string.to_uppercase())),
+        "toLowerCase" => Ok(ExpressionResult::String(string.to_lowercase())),
+        "trim" => Ok(ExpressionResult::String(string.trim().to_string())),
+        // the argument is converted to a string, so "undefined".includes() is true
+        "includes" => {
+            let search = arguments.first().cloned().unwrap_or(ExpressionResult::Undefined);
+            Ok(ExpressionResult::Boolean(string.contains(search.coerce_to_string().as_str())))
+        }
+        _ => Err(not_a_function(method)),
+    }
+}
+
+fn call_number_method(
+    number: f64,
+    method: &str,
+    arguments: Vec<ExpressionResult>,
+) -> Result<ExpressionResult, String> {
+    match method {
+        "toString" => {
+            let radix = match arguments.first() {
+                None | Some(ExpressionResult::Undefined) => 10.0,
+                Some(radix) => radix.coerce_to_number().unwrap_or(f64::NAN).trunc(),
+            };
+            if !(2.0..=36.0).contains(&radix) {
+                return Err(InterpreterError {
+                    kind: InterpreterErrorKind::RangeError("toString() radix must be between 2 and 36".into()),
+                }
+                .to_string());
+            }
+            if radix == 10.0 || !number.is_finite() {
+                return Ok(ExpressionResult::String(ExpressionResult::Number(number).coerce_to_string()));
+            }
+            Ok(ExpressionResult::String(to_radix_string(number, radix as u32)))
+        }
+        "toFixed" => {
+            let digits = match arguments.first() {
+                None | Some(ExpressionResult::Undefined) => 0.0,
+                Some(digits) => digits.coerce_to_number().unwrap_or(0.0).trunc(),
+            };
+            if !(0.0..=100.0).contains(&digits) {
+                return Err(InterpreterError {
+                    kind: InterpreterErrorKind::RangeError("toFixed() digits argument must be between 0 and 100".into()),
+                }
+                .to_string());
+            }
+            // like JS, numbers too large for fixed notation are written as they usually would be
+            if !number.is_finite() || number.abs() >= 1e21 {
+                return Ok(ExpressionResult::String(ExpressionResult::Number(number).coerce_to_string()));
+            }
+            Ok(ExpressionResult::String(to_fixed_string(number, digits as usize)))
+        }
+        _ => Err(not_a_function(method)),
+    }
+}
+
+// Most fractions never end in another base, so only this many digits are written after the point
+const MAX_FRACTION_DIGITS: usize = 20;
+
+/// Writes a finite number in the given base, such as 255 in base 16 as "ff"
+fn to_radix_string(number: f64, radix: u32) -> String {
+    let mut integer = number.abs().trunc();
+    let mut fraction = number.abs().fract();
+    let mut digits = vec![];
+    loop {
+        let digit = (integer % radix as f64) as u32;
+        digits.push(std::char::from_digit(digit, radix).unwrap());
+        integer = (integer / radix as f64).trunc();
+        if integer == 0.0 {
+            break;
+        }
+    }
+    if number < 0.0 {
+        digits.push('-');
+    }
+    let mut result: String = digits.into_iter().rev().collect();
+    if fraction > 0.0 {
+        result.push('.');
+        for _ in 0..MAX_FRACTION_DIGITS {
+            fraction *= radix as f64;
+            let digit = fraction.trunc() as u32;
+            result.push(std::char::from_digit(digit, radix).unwrap());
+            fraction = fraction.fract();
+            if fraction == 0.0 {
+                break;
+            }
+        }
+    }
+    result
+}
+
+// Enough decimal places to write any f64 exactly, the smallest of which has 1074
+const EXACT_DECIMAL_PLACES: usize = 1100;
+
+/// Writes a number with a fixed count of decimal places.  Rust's formatting rounds exact ties to even,
+/// but JS rounds them away from zero, so (2.5).toFixed(0) is "3".  The number is written out exactly
+/// first, so only true ties round up, and 1.005, which is really 1.00499..., still gives "1.00".
+fn to_fixed_string(number: f64, digits: usize) -> String {
+    let exact = format!("{:.*}", EXACT_DECIMAL_PLACES, number.abs());
+    let point = exact.find('.').unwrap();
+    let rounds_up = exact.as_bytes()[point + 1 + digits] >= b'5';
+    let mut kept: Vec<u8> = exact.as_bytes()[..point + 1 + digits].to_vec();
+    if digits == 0 {
+        kept.pop();
+    }
+    if rounds_up {
+        let mut position = kept.len();
+        loop {
+            if position == 0 {
+                kept.insert(0, b'1');
+                break;
+            }
+            position -= 1;
+            match kept[position] {
+                b'.' => continue,
+                b'9' => kept[position] = b'0',
+                digit => {
+                    kept[position] = digit + 1;
+                    break;
+                }
+            }
+        }
+    }
+    let result = String::from_utf8(kept).unwrap();
+    // -0.001 rounds to zero, which JS writes without a sign
+    if number < 0.0 && result.bytes().any(|digit| (b'1'..=b'9').contains(&digit)) {
+        format!("-{}", result)
+    } else {
+        result
+    }
+}
+
+/// Resolves a position given to slice, where negative positions count back from the end.
+/// Positions are clamped to the string, and a missing position is `default`.
+fn relative_position(length: usize, position: Option<&ExpressionResult>, default: usize) -> usize {
+    let position = match position {
+        None | Some(ExpressionResult::Undefined) => return default,
+        Some(position) => position.coerce_to_number().unwrap_or(f64::NAN),
+    };
+    let position = if position.is_nan() { 0.0 } else { position.trunc() };
+    let position = if position < 0.0 { length as f64 + position } else { position };
+    position.clamp(0.0, length as f64) as usize
+}
+
+/// Resolves a position given to substring, which clamps negative positions to 0 instead
+fn clamped_position(length: usize, position: Option<&ExpressionResult>, default: usize) -> usize {
+    match position {
+        None | Some(ExpressionResult::Undefined) => default,
+        Some(position) => {
+            let position = position.coerce_to_number().unwrap_or(f64::NAN);
+            if position.is_nan() { 0 } else { position.trunc().clamp(0.0, length as f64) as usize }
+        }
+    }
+}
+
+// Counts in characters rather than bytes, so multi-byte characters are never split
+fn substring(string: &str, start: usize, end: usize) -> String {
+    string.chars().skip(start).take(end.saturating_sub(start)).collect()
+}
+
+/// Resolves the index given to `at`, where negative indices count back from the end.
+/// Returns None when the index is out of range.
+fn relative_index(length: usize, index: Option<&ExpressionResult>) -> Option<usize> {
+    let index = index
+        .map(|index| index.coerce_to_number().unwrap_or(f64::NAN))
+        .unwrap_or(0.0);
+    // NaN and missing indices read the first element, fractions are truncated
+    let index = if index.is_nan() { 0.0 } else { index.trunc() };
+    let position = if index < 0.0 { length as f64 + index } else { index };
+    if position < 0.0 || position >= length as f64 {
+        return None;
+    }
+    Some(position as usize)
+}
+
+fn not_a_function(method: &str) -> String {
+    InterpreterError {
+        kind: InterpreterErrorKind::TypeError(format!("{} is not a function", method)),
+    }
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: Vec<&str>) -> ExpressionResult {
+        ExpressionResult::Array(
+            values
+                .into_iter()
+                .map(|value| ExpressionResult::String(value.into()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn split_should_separate_on_separator() {
+        let result = call_method(
+            &mut ExpressionResult::String("a,b,c".into()),
+            "split",
+            vec![ExpressionResult::String(",".into())],
+        );
+        assert_eq!(result, Ok(strings(vec!["a", "b", "c"])));
+    }
+
+    #[test]
+    fn split_should_separate_characters_on_empty_separator() {
+        let result = call_method(
+            &mut ExpressionResult::String("abc".into()),
+            "split",
+            vec![ExpressionResult::String("".into())],
+        );
+        assert_eq!(result, Ok(strings(vec!["a", "b", "c"])));
+    }
+
+    #[test]
+    fn split_without_separator_should_return_whole_string() {
+        let result = call_method(&mut ExpressionResult::String("a,b".into()), "split", vec![]);
+        assert_eq!(result, Ok(strings(vec!["a,b"])));
+    }
+
+    #[test]
+    fn array_length_should_count_elements() {
+        let array = ExpressionResult::Array(vec![ExpressionResult::Boolean(true)]);
+        assert_eq!(get_property(&array, "length"), ExpressionResult::Number(1.0));
+    }
+
+    #[test]
+    fn push_should_append_and_return_new_length() {
+        let mut array = ExpressionResult::Array(vec![ExpressionResult::Number(1.0)]);
+        let result = call_method(&mut array, "push", vec![ExpressionResult::Number(2.0)]);
+        assert_eq!(result, Ok(ExpressionResult::Number(2.0)));
+        assert_eq!(
+            array,
+            ExpressionResult::Array(vec![ExpressionResult::Number(1.0), ExpressionResult::Number(2.0)])
+        );
+    }
+
+    #[test]
+    fn pop_should_remove_and_return_last_element() {
+        let mut array = ExpressionResult::Array(vec![ExpressionResult::Number(1.0), ExpressionResult::Number(2.0)]);
+        let result = call_method(&mut array, "pop", vec![]);
+        assert_eq!(result, Ok(ExpressionResult::Number(2.0)));
+        assert_eq!(array, ExpressionResult::Array(vec![ExpressionResult::Number(1.0)]));
+    }
+
+    #[test]
+    fn pop_on_empty_array_should_return_undefined() {
+        let mut array = ExpressionResult::Array(vec![]);
+        let result = call_method(&mut array, "pop", vec![]);
+        assert_eq!(result, Ok(ExpressionResult::Undefined));
+    }
+
+    #[test]
+    fn string_length_should_count_characters() {
+        let string = ExpressionResult::String("héllo".into());
+        assert_eq!(get_property(&string, "length"), ExpressionResult::Number(5.0));
+    }
+
+    #[test]
+    fn empty_string_length_should_be_zero() {
+        let string = ExpressionResult::String("".into());
+        assert_eq!(get_property(&string, "length"), ExpressionResult::Number(0.0));
+    }
+
+    #[test]
+    fn string_index_should_return_character() {
+        let string = ExpressionResult::String("hello".into());
+        assert_eq!(
+            get_index(&string, &ExpressionResult::Number(1.0)),
+            ExpressionResult::String("e".into())
+        );
+    }
+
+    #[test]
+    fn string_index_out_of_range_should_be_undefined() {
+        let string = ExpressionResult::String("".into());
+        assert_eq!(
+            get_index(&string, &ExpressionResult::Number(0.0)),
+            ExpressionResult::Undefined
+        );
+    }
+
+    #[test]
+    fn numeric_string_index_should_read_like_a_number() {
+        let string = ExpressionResult::String("abc".into());
+        let array = ExpressionResult::Array(vec![ExpressionResult::Number(1.0), ExpressionResult::Number(2.0)]);
+        let index = |key: &str| ExpressionResult::String(key.into());
+        assert_eq!(get_index(&string, &index("1")), ExpressionResult::String("b".into()));
+        assert_eq!(get_index(&array, &index("1")), ExpressionResult::Number(2.0));
+        // only numbers written the way JS writes them count as indices
+        assert_eq!(get_index(&string, &index("01")), ExpressionResult::Undefined);
+        assert_eq!(get_index(&string, &index("1.0")), ExpressionResult::Undefined);
+        assert_eq!(get_index(&string, &index(" 1")), ExpressionResult::Undefined);
+        assert_eq!(get_index(&string, &index("length")), ExpressionResult::Number(3.0));
+    }
+
+    #[test]
+    fn negative_string_index_should_be_undefined() {
+        let string = ExpressionResult::String("hello".into());
+        assert_eq!(
+            get_index(&string, &ExpressionResult::Number(-1.0)),
+            ExpressionResult::Undefined
+        );
+    }
+
+    #[test]
+    fn unknown_method_should_throw_type_error() {
+        let result = call_method(&mut ExpressionResult::Number(1.0), "split", vec![]);
+        assert_eq!(
+            result,
+            Err(InterpreterError {
+                kind: InterpreterErrorKind::TypeError("split is not a function".into())
+            }
+            .to_string())
+        );
+    }
+
+    #[test]
+    fn at_should_count_negative_indices_from_the_end() {
+        let mut array = ExpressionResult::Array(vec![ExpressionResult::Number(1.0), ExpressionResult::Number(2.0)]);
+        let first = call_method(&mut array, "at", vec![ExpressionResult::Number(0.0)]);
+        let last = call_method(&mut array, "at", vec![ExpressionResult::Number(-1.0)]);
+        assert_eq!(first, Ok(ExpressionResult::Number(1.0)));
+        assert_eq!(last, Ok(ExpressionResult::Number(2.0)));
+    }
+
+    #[test]
+    fn at_out_of_range_should_be_undefined() {
+        let mut array = ExpressionResult::Array(vec![ExpressionResult::Number(1.0)]);
+        let after = call_method(&mut array, "at", vec![ExpressionResult::Number(100.0)]);
+        let before = call_method(&mut array, "at", vec![ExpressionResult::Number(-2.0)]);
+        assert_eq!(after, Ok(ExpressionResult::Undefined));
+        assert_eq!(before, Ok(ExpressionResult::Undefined));
+    }
+
+    #[test]
+    fn string_at_should_return_character_from_the_end() {
+        let result = call_method(
+            &mut ExpressionResult::String("abc".into()),
+            "at",
+            vec![ExpressionResult::Number(-1.0)],
+        );
+        assert_eq!(result, Ok(ExpressionResult::String("c".into())));
+    }
+
+    #[test]
+    fn set_index_should_fill_gaps_in_arrays_with_undefined() {
+        let mut array = strings(vec!["a"]);
+        set_index(&mut array, &ExpressionResult::Number(2.0), ExpressionResult::String("c".into())).unwrap();
+        assert_eq!(
+            array,
+            ExpressionResult::Array(vec![
+                ExpressionResult::String("a".into()),
+                ExpressionResult::Undefined,
+                ExpressionResult::String("c".into()),
+            ])
+        );
+    }
+
+    #[test]
+    fn set_index_should_reject_undefined() {
+        let result = set_index(&mut ExpressionResult::Undefined, &ExpressionResult::String("x".into()), ExpressionResult::Null);
+        assert_eq!(result, Err("Uncaught TypeError: Cannot set properties of undefined (setting 'x')".to_string()));
+    }
+
+    #[test]
+    fn number_to_string_should_convert_to_the_given_radix() {
+        let mut number = ExpressionResult::Number(255.0);
+        let binary = call_method(&mut number, "toString", vec![ExpressionResult::Number(2.0)]);
+        let octal = call_method(&mut number, "toString", vec![ExpressionResult::Number(8.0)]);
+        let hexadecimal = call_method(&mut number, "toString", vec![ExpressionResult::Number(16.0)]);
+        assert_eq!(binary, Ok(ExpressionResult::String("11111111".into())));
+        assert_eq!(octal, Ok(ExpressionResult::String("377".into())));
+        assert_eq!(hexadecimal, Ok(ExpressionResult::String("ff".into())));
+    }
+
+    #[test]
+    fn number_to_string_should_convert_negatives_and_fractions() {
+        let negative = call_method(&mut ExpressionResult::Number(-255.0), "toString", vec![ExpressionResult::Number(16.0)]);
+        let fraction = call_method(&mut ExpressionResult::Number(2.5), "toString", vec![ExpressionResult::Number(2.0)]);
+        let decimal = call_method(&mut ExpressionResult::Number(2.5), "toString", vec![]);
+        assert_eq!(negative, Ok(ExpressionResult::String("-ff".into())));
+        assert_eq!(fraction, Ok(ExpressionResult::String("10.1".into())));
+        assert_eq!(decimal, Ok(ExpressionResult::String("2.5".into())));
+    }
+
+    #[test]
+    fn number_to_string_should_reject_radix_out_of_range() {
+        let result = call_method(&mut ExpressionResult::Number(1.0), "toString", vec![ExpressionResult::Number(37.0)]);
+        assert_eq!(
+            result,
+            Err(InterpreterError {
+                kind: InterpreterErrorKind::RangeError("toString() radix must be between 2 and 36".into())
+            }
+            .to_string())
+        );
+    }
+
+    #[test]
+    fn to_fixed_should_round_ties_away_from_zero() {
+        let to_fixed = |number: f64, digits: f64| {
+            call_method(&mut ExpressionResult::Number(number), "toFixed", vec![ExpressionResult::Number(digits)])
+        };
+        assert_eq!(to_fixed(0.1, 1.0), Ok(ExpressionResult::String("0.1".into())));
+        assert_eq!(to_fixed(2.5, 0.0), Ok(ExpressionResult::String("3".into())));
+        assert_eq!(to_fixed(-2.5, 0.0), Ok(ExpressionResult::String("-3".into())));
+        assert_eq!(to_fixed(0.25, 1.0), Ok(ExpressionResult::String("0.3".into())));
+        assert_eq!(to_fixed(9.995, 2.0), Ok(ExpressionResult::String("9.99".into())));
+        assert_eq!(to_fixed(99.5, 0.0), Ok(ExpressionResult::String("100".into())));
+        assert_eq!(to_fixed(1.0, 2.0), Ok(ExpressionResult::String("1.00".into())));
+    }
+
+    #[test]
+    fn to_fixed_should_keep_the_floating_point_value() {
+        let to_fixed = |number: f64, digits: f64| {
+            call_method(&mut ExpressionResult::Number(number), "toFixed", vec![ExpressionResult::Number(digits)])
+        };
+        // 1.005 is stored as 1.00499999999999989..., so it rounds down
+        assert_eq!(to_fixed(1.005, 2.0), Ok(ExpressionResult::String("1.00".into())));
+        assert_eq!(to_fixed(0.1 + 0.2, 20.0), Ok(ExpressionResult::String("0.30000000000000004441".into())));
+        assert_eq!(to_fixed(-0.0001, 2.0), Ok(ExpressionResult::String("0.00".into())));
+    }
+
+    #[test]
+    fn string_case_methods_should_convert_every_letter() {
+        let mut string = ExpressionResult::String("Hello, World".into());
+        assert_eq!(call_method(&mut string, "toUpperCase", vec![]), Ok(ExpressionResult::String("HELLO, WORLD".into())));
+        assert_eq!(call_method(&mut string, "toLowerCase", vec![]), Ok(ExpressionResult::String("hello, world".into())));
+        assert_eq!(string, ExpressionResult::String("Hello, World".into()));
+    }
+
+    #[test]
+    fn trim_should_remove_surrounding_whitespace() {
+        let result = call_method(&mut ExpressionResult::String("  hi  ".into()), "trim", vec![]);
+        assert_eq!(result, Ok(ExpressionResult::String("hi".into())));
+    }
+
+    #[test]
+    fn includes_should_search_for_a_substring() {
+        let mut string = ExpressionResult::String("abc".into());
+        let found = call_method(&mut string, "includes", vec![ExpressionResult::String("b".into())]);
+        let missing = call_method(&mut string, "includes", vec![ExpressionResult::String("d".into())]);
+        let empty = call_method(&mut string, "includes", vec![ExpressionResult::String("".into())]);
+        assert_eq!(found, Ok(ExpressionResult::Boolean(true)));
+        assert_eq!(missing, Ok(ExpressionResult::Boolean(false)));
+        assert_eq!(empty, Ok(ExpressionResult::Boolean(true)));
+    }
+
+    #[test]
+    fn slice_should_count_negative_positions_from_the_end() {
+        let slice = |arguments: Vec<f64>| {
+            call_method(
+                &mut ExpressionResult::String("hello".into()),
+                "slice",
+                arguments.into_iter().map(ExpressionResult::Number).collect(),
+            )
+        };
+        assert_eq!(slice(vec![1.0, 3.0]), Ok(ExpressionResult::String("el".into())));
+        assert_eq!(slice(vec![1.0]), Ok(ExpressionResult::String("ello".into())));
+        assert_eq!(slice(vec![-2.0]), Ok(ExpressionResult::String("lo".into())));
+        assert_eq!(slice(vec![-10.0, 2.0]), Ok(ExpressionResult::String("he".into())));
+        assert_eq!(slice(vec![3.0, 1.0]), Ok(ExpressionResult::String("".into())));
+    }
+
+    #[test]
+    fn substring_should_swap_positions_when_start_is_after_end() {
+        let substring = |arguments: Vec<f64>| {
+            call_method(
+                &mut ExpressionResult::String("hello".into()),
+                "substring",
+                arguments.into_iter().map(ExpressionResult::Number).collect(),
+            )
+        };
+        assert_eq!(substring(vec![1.0, 3.0]), Ok(ExpressionResult::String("el".into())));
+        assert_eq!(substring(vec![3.0, 1.0]), Ok(ExpressionResult::String("el".into())));
+        assert_eq!(substring(vec![2.0]), Ok(ExpressionResult::String("llo".into())));
+        assert_eq!(substring(vec![-2.0, 2.0]), Ok(ExpressionResult::String("he".into())));
+    }
+
+    #[test]
+    fn slice_should_not_split_multi_byte_characters() {
+        let result = call_method(
+            &mut ExpressionResult::String("héllo".into()),
+            "slice",
+            vec![ExpressionResult::Number(1.0), ExpressionResult::Number(2.0)],
+        );
+        assert_eq!(result, Ok(ExpressionResult::String("é".into())));
+    }
+
+    #[test]
+    fn join_should_join_nested_arrays_with_commas() {
+        let mut array = ExpressionResult::Array(vec![
+            ExpressionResult::Array(vec![ExpressionResult::Number(1.0), ExpressionResult::Number(2.0)]),
+            ExpressionResult::Array(vec![ExpressionResult::Number(3.0)]),
+            ExpressionResult::Null,
+        ]);
+        let result = call_method(&mut array, "join", vec![ExpressionResult::String("-".into())]);
+        assert_eq!(result, Ok(ExpressionResult::String("1,2-3-".into())));
+    }
+
+    #[test]
+    fn join_should_write_null_and_undefined_as_empty_strings() {
+        let mut array = ExpressionResult::Array(vec![
+            ExpressionResult::Null,
+            ExpressionResult::Number(1.0),
+            ExpressionResult::Undefined,
+        ]);
+        assert_eq!(call_method(&mut array, "join", vec![]), Ok(ExpressionResult::String(",1,".into())));
+        assert_eq!(array.coerce_to_string(), ",1,");
+    }
+}
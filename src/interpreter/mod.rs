@@ -1,6 +1,10 @@
 pub mod errors;
 pub mod interpreter;
+pub mod json;
+pub mod methods;
+pub mod natives;
 pub mod operators;
+pub mod output;
 pub mod visitor;
 
 pub use interpreter::*;
\ No newline at end of file
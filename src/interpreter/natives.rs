@@ -0,0 +1,533 @@
+use std::fmt::Debug;
+
+use crate::ast::ExpressionResult;
+use crate::environment::Environment;
+use crate::interpreter::errors::{InterpreterError, InterpreterErrorKind};
+use crate::interpreter::json;
+use crate::object::Object;
+
+pub type NativeFn = fn(Vec<ExpressionResult>) -> Result<ExpressionResult, String>;
+
+/// A function implemented in Rust rather than in script.
+///
+/// Natives are plain values, so they are registered as global variables and
+/// can be called like any declared function.
+#[derive(Clone)]
+pub struct NativeFunction {
+    name: String,
+    function: NativeFn,
+}
+
+impl NativeFunction {
+    pub fn new(name: &str, function: NativeFn) -> Self {
+        NativeFunction {
+            name: name.to_string(),
+            function,
+        }
+    }
+
+    pub fn call(&self, arguments: Vec<ExpressionResult>) -> Result<ExpressionResult, String> {
+        (self.function)(arguments)
+    }
+}
+
+// Two natives are the same function if they were registered under the same name
+impl PartialEq for NativeFunction {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Debug for NativeFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "function {}() {{ [native code] }}", self.name)
+    }
+}
+
+pub fn register_natives(env: &mut Environment) {
+    let natives: Vec<(&str, NativeFn)> = vec![
+        ("range", range),
+        ("parseFloat", parse_float),
+        ("deepEqual", deep_equal),
+        ("Boolean", boolean),
+        ("Number", number),
+        ("String", string),
+        ("isNaN", is_nan),
+        ("isFinite", is_finite),
+    ];
+    for (name, function) in natives {
+        env.define_variable(
+            name.to_string(),
+            ExpressionResult::NativeFunction(NativeFunction::new(name, function)),
+        );
+    }
+
+    let globals: Vec<(&str, Vec<(&str, NativeFn)>)> = vec![
+        ("JSON", vec![("stringify", json::stringify), ("parse", json::parse)]),
+        (
+            "Object",
+            vec![("keys", object_keys), ("getOwnPropertyNames", object_keys), ("is", object_is)],
+        ),
+        ("Math", vec![("fround", math_fround)]),
+    ];
+    for (name, methods) in globals {
+        let mut object = Object::new();
+        for (method, function) in methods {
+            object.set(
+                method.to_string(),
+                ExpressionResult::NativeFunction(NativeFunction::new(method, function)),
+            );
+        }
+        env.define_variable(name.to_string(), ExpressionResult::Object(object));
+    }
+}
+
+fn get_argument(arguments: &[ExpressionResult], index: usize) -> ExpressionResult {
+    arguments
+        .get(index)
+        .cloned()
+        .unwrap_or(ExpressionResult::Undefined)
+}
+
+fn number_argument(arguments: &[ExpressionResult], index: usize) -> Result<f64, String> {
+    get_argument(arguments, index)
+        .coerce_to_number()
+        .map_err(|_| {
+            InterpreterError {
+                kind: InterpreterErrorKind::NaN,
+            }
+            .to_string()
+        })
+}
+
+/// range(start, end, step) builds an array counting from start (inclusive) to end (exclusive).
+/// The step defaults to 1 and may be negative to count down.
+fn range(arguments: Vec<ExpressionResult>) -> Result<ExpressionResult, String> {
+    let start = number_argument(&arguments, 0)?;
+    let end = number_argument(&arguments, 1)?;
+    let step = match get_argument(&arguments, 2) {
+        ExpressionResult::Undefined => 1.0,
+        _ => number_argument(&arguments, 2)?,
+    };
+    if step == 0.0 {
+        return Err(InterpreterError {
+            kind: InterpreterErrorKind::RangeError("range step cannot be zero".into()),
+        }
+        .to_string());
+    }
+
+    let mut elements = vec![];
+    let mut current = start;
+    while (step > 0.0 && current < end) || (step < 0.0 && current > end) {
+        elements.push(ExpressionResult::Number(current));
+        current += step;
+    }
+    Ok(ExpressionResult::Array(elements))
+}
+
+/// Boolean(value) converts a value the way conditions do, so Boolean(0) is false and Boolean("x") is true
+fn boolean(arguments: Vec<ExpressionResult>) -> Result<ExpressionResult, String> {
+    Ok(ExpressionResult::Boolean(get_argument(&arguments, 0).coerce_to_bool()))
+}
+
+/// Number(value) converts a value the way arithmetic does, giving NaN when it isn't a number.
+/// Number() with no value at all is 0 rather than NaN.
+fn number(arguments: Vec<ExpressionResult>) -> Result<ExpressionResult, String> {
+    if arguments.is_empty() {
+        return Ok(ExpressionResult::Number(0.0));
+    }
+    Ok(ExpressionResult::Number(get_argument(&arguments, 0).to_number()))
+}
+
+/// String(value) converts a value the way concatenation does, and String() with no value is ""
+fn string(arguments: Vec<ExpressionResult>) -> Result<ExpressionResult, String> {
+    if arguments.is_empty() {
+        return Ok(ExpressionResult::String(String::new()));
+    }
+    Ok(ExpressionResult::String(get_argument(&arguments, 0).coerce_to_string()))
+}
+
+/// isNaN(value) converts the value to a number first, so isNaN("abc") is true while isNaN("5") is false
+fn is_nan(arguments: Vec<ExpressionResult>) -> Result<ExpressionResult, String> {
+    let number = get_argument(&arguments, 0).to_number();
+    Ok(ExpressionResult::Boolean(number.is_nan()))
+}
+
+/// isFinite(value) converts the value to a number first, and is false for NaN as well as the infinities
+fn is_finite(arguments: Vec<ExpressionResult>) -> Result<ExpressionResult, String> {
+    let number = get_argument(&arguments, 0).to_number();
+    Ok(ExpressionResult::Boolean(number.is_finite()))
+}
+
+/// Object.keys(value) lists the own property names of a value in insertion order.
+/// Every property is enumerable here, so this also serves as Object.getOwnPropertyNames.
+fn object_keys(arguments: Vec<ExpressionResult>) -> Result<ExpressionResult, String> {
+    let keys: Vec<String> = match get_argument(&arguments, 0) {
+        ExpressionResult::Object(object) => object.keys(),
+        ExpressionResult::Array(elements) => (0..elements.len()).map(|index| index.to_string()).collect(),
+        ExpressionResult::String(string) => (0..string.chars().count()).map(|index| index.to_string()).collect(),
+        ExpressionResult::Null | ExpressionResult::Undefined => {
+            return Err(InterpreterError {
+                kind: InterpreterErrorKind::TypeError("Cannot convert undefined or null to object".into()),
+            }
+            .to_string())
+        }
+        _ => vec![],
+    };
+    Ok(ExpressionResult::Array(keys.into_iter().map(ExpressionResult::String).collect()))
+}
+
+/// Object.is(a, b) is like === except that NaN is the same as NaN, and 0 and -0 are different
+fn object_is(arguments: Vec<ExpressionResult>) -> Result<ExpressionResult, String> {
+    let same = match (get_argument(&arguments, 0), get_argument(&arguments, 1)) {
+        (ExpressionResult::Number(l), ExpressionResult::Number(r)) => {
+            (l == r && l.is_sign_negative() == r.is_sign_negative()) || (l.is_nan() && r.is_nan())
+        }
+        (left, right) => left == right,
+    };
+    Ok(ExpressionResult::Boolean(same))
+}
+
+/// Math.fround(x) rounds a number to the nearest value a 32 bit float can hold, so Math.fround(1.1) is 1.100000023841858
+fn math_fround(arguments: Vec<ExpressionResult>) -> Result<ExpressionResult, String> {
+    let number = get_argument(&arguments, 0).coerce_to_number().unwrap_or(f64::NAN);
+    Ok(ExpressionResult::Number(number as f32 as f64))
+}
+
+/// deepEqual(a, b) compares arrays and objects by their contents rather than by identity.
+fn deep_equal(arguments: Vec<ExpressionResult>) -> Result<ExpressionResult, String> {
+    let left = get_argument(&arguments, 0);
+    let right = get_argument(&arguments, 1);
+    Ok(ExpressionResult::Boolean(structurally_equal(&left, &right)))
+}
+
+/// Compares arrays element by element and objects key by key, ignoring the order keys were added in.
+/// Primitives must have the same type and value, although NaN equals NaN here.
+fn structurally_equal(left: &ExpressionResult, right: &ExpressionResult) -> bool {
+    match (left, right) {
+        (ExpressionResult::Number(l), ExpressionResult::Number(r)) => {
+            l == r || (l.is_nan() && r.is_nan())
+        }
+        (ExpressionResult::Array(l), ExpressionResult::Array(r)) => {
+            l.len() == r.len()
+                && l.iter()
+                    .zip(r.iter())
+                    .all(|(l, r)| structurally_equal(l, r))
+        }
+        (ExpressionResult::Object(l), ExpressionResult::Object(r)) => {
+            l.keys().len() == r.keys().len()
+                && l.entries().all(|(key, value)| {
+                    r.get(key)
+                        .is_some_and(|other| structurally_equal(value, other))
+                })
+        }
+        _ => left == right,
+    }
+}
+
+/// parseFloat(string) reads the longest prefix of the string that is a valid number,
+/// so "3.14e2abc" is 314.  Leading whitespace is skipped, and a string with no numeric prefix is NaN.
+fn parse_float(arguments: Vec<ExpressionResult>) -> Result<ExpressionResult, String> {
+    let string = get_argument(&arguments, 0).coerce_to_string();
+    let prefix = float_prefix(string.trim_start());
+    let number = match prefix.trim_start_matches(['+', '-']) {
+        "" => f64::NAN,
+        "Infinity" if prefix.starts_with('-') => f64::NEG_INFINITY,
+        "Infinity" => f64::INFINITY,
+        _ => prefix.parse::<f64>().unwrap_or(f64::NAN),
+    };
+    Ok(ExpressionResult::Number(number))
+}
+
+// Returns the longest prefix of the form [sign](Infinity | digits[.digits][e[sign]digits]),
+// where either side of the decimal point may be empty but not both
+fn float_prefix(string: &str) -> &str {
+    let characters: Vec<char> = string.chars().collect();
+    let mut end = 0;
+    if matches!(characters.first(), Some('+' | '-')) {
+        end += 1;
+    }
+    if string[end..].starts_with("Infinity") {
+        return &string[..end + "Infinity".len()];
+    }
+
+    let count_digits = |from: usize| {
+        characters[from..]
+            .iter()
+            .take_while(|character| character.is_ascii_digit())
+            .count()
+    };
+    let integer_digits = count_digits(end);
+    end += integer_digits;
+    let mut fraction_digits = 0;
+    if characters.get(end) == Some(&'.') {
+        fraction_digits = count_digits(end + 1);
+        if integer_digits > 0 || fraction_digits > 0 {
+            end += 1 + fraction_digits;
+        }
+    }
+    if integer_digits == 0 && fraction_digits == 0 {
+        return "";
+    }
+
+    // an exponent only counts if at least one digit follows it
+    if matches!(characters.get(end), Some('e' | 'E')) {
+        let mut exponent_end = end + 1;
+        if matches!(characters.get(exponent_end), Some('+' | '-')) {
+            exponent_end += 1;
+        }
+        let exponent_digits = count_digits(exponent_end);
+        if exponent_digits > 0 {
+            end = exponent_end + exponent_digits;
+        }
+    }
+    // every character accepted so far is ASCII, so the character count is also a byte offset
+    &string[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::Block;
+    use crate::function::Function;
+
+    fn numbers(values: Vec<f64>) -> ExpressionResult {
+        ExpressionResult::Array(values.into_iter().map(ExpressionResult::Number).collect())
+    }
+
+    #[test]
+    fn range_should_count_up_by_one() {
+        let arguments = vec![ExpressionResult::Number(0.0), ExpressionResult::Number(5.0)];
+        let result = range(arguments).unwrap();
+        assert_eq!(result, numbers(vec![0.0, 1.0, 2.0, 3.0, 4.0]));
+    }
+
+    #[test]
+    fn range_should_count_down_with_negative_step() {
+        let arguments = vec![
+            ExpressionResult::Number(5.0),
+            ExpressionResult::Number(0.0),
+            ExpressionResult::Number(-1.0),
+        ];
+        let result = range(arguments).unwrap();
+        assert_eq!(result, numbers(vec![5.0, 4.0, 3.0, 2.0, 1.0]));
+    }
+
+    #[test]
+    fn range_should_respect_step() {
+        let arguments = vec![
+            ExpressionResult::Number(0.0),
+            ExpressionResult::Number(5.0),
+            ExpressionResult::Number(2.0),
+        ];
+        let result = range(arguments).unwrap();
+        assert_eq!(result, numbers(vec![0.0, 2.0, 4.0]));
+    }
+
+    #[test]
+    fn range_should_throw_range_error_on_zero_step() {
+        let arguments = vec![
+            ExpressionResult::Number(0.0),
+            ExpressionResult::Number(5.0),
+            ExpressionResult::Number(0.0),
+        ];
+        let result = range(arguments);
+        assert_eq!(
+            result,
+            Err(InterpreterError {
+                kind: InterpreterErrorKind::RangeError("range step cannot be zero".into())
+            }
+            .to_string())
+        );
+    }
+
+    #[test]
+    fn object_keys_should_list_array_indices() {
+        let array = ExpressionResult::Array(vec![ExpressionResult::Null, ExpressionResult::Null]);
+        assert_eq!(
+            object_keys(vec![array]),
+            Ok(ExpressionResult::Array(vec![
+                ExpressionResult::String("0".into()),
+                ExpressionResult::String("1".into())
+            ]))
+        );
+    }
+
+    #[test]
+    fn object_keys_should_throw_type_error_on_undefined() {
+        assert_eq!(
+            object_keys(vec![]),
+            Err(InterpreterError {
+                kind: InterpreterErrorKind::TypeError("Cannot convert undefined or null to object".into())
+            }
+            .to_string())
+        );
+    }
+
+    fn parsed(string: &str) -> f64 {
+        match parse_float(vec![ExpressionResult::String(string.into())]).unwrap() {
+            ExpressionResult::Number(number) => number,
+            other => panic!("parseFloat returned {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_float_should_stop_at_first_invalid_character_after_exponent() {
+        assert_eq!(parsed("3.14e2abc"), 314.0);
+    }
+
+    #[test]
+    fn parse_float_should_accept_leading_dot() {
+        assert_eq!(parsed(".5"), 0.5);
+    }
+
+    #[test]
+    fn parse_float_should_read_infinity() {
+        assert_eq!(parsed("Infinity"), f64::INFINITY);
+        assert_eq!(parsed("-Infinityx"), f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn parse_float_should_return_nan_without_numeric_prefix() {
+        assert!(parsed("abc").is_nan());
+        assert!(parsed(".").is_nan());
+    }
+
+    #[test]
+    fn parse_float_should_ignore_incomplete_exponent() {
+        assert_eq!(parsed("  12e+"), 12.0);
+    }
+
+    fn object(entries: Vec<(&str, ExpressionResult)>) -> ExpressionResult {
+        let mut object = Object::new();
+        for (key, value) in entries {
+            object.set(key.to_string(), value);
+        }
+        ExpressionResult::Object(object)
+    }
+
+    #[test]
+    fn deep_equal_should_match_objects_with_the_same_entries_in_any_order() {
+        let left = object(vec![("a", ExpressionResult::Number(1.0)), ("b", ExpressionResult::Null)]);
+        let right = object(vec![("b", ExpressionResult::Null), ("a", ExpressionResult::Number(1.0))]);
+        assert_eq!(deep_equal(vec![left, right]), Ok(ExpressionResult::Boolean(true)));
+    }
+
+    #[test]
+    fn deep_equal_should_reject_differing_objects() {
+        let left = object(vec![("a", ExpressionResult::Number(1.0))]);
+        let right = object(vec![("a", ExpressionResult::String("1".to_string()))]);
+        let missing = object(vec![]);
+        assert_eq!(deep_equal(vec![left.clone(), right]), Ok(ExpressionResult::Boolean(false)));
+        assert_eq!(deep_equal(vec![left, missing]), Ok(ExpressionResult::Boolean(false)));
+    }
+
+    #[test]
+    fn deep_equal_should_recurse_into_nested_arrays() {
+        let nested = |last: f64| {
+            ExpressionResult::Array(vec![numbers(vec![1.0, 2.0]), numbers(vec![last])])
+        };
+        assert_eq!(deep_equal(vec![nested(3.0), nested(3.0)]), Ok(ExpressionResult::Boolean(true)));
+        assert_eq!(deep_equal(vec![nested(3.0), nested(4.0)]), Ok(ExpressionResult::Boolean(false)));
+    }
+
+    #[test]
+    fn object_is_should_use_same_value_equality() {
+        let is = |left: f64, right: f64| object_is(vec![ExpressionResult::Number(left), ExpressionResult::Number(right)]);
+        assert_eq!(is(f64::NAN, f64::NAN), Ok(ExpressionResult::Boolean(true)));
+        assert_eq!(is(0.0, -0.0), Ok(ExpressionResult::Boolean(false)));
+        assert_eq!(is(1.0, 1.0), Ok(ExpressionResult::Boolean(true)));
+    }
+
+    #[test]
+    fn object_is_should_not_coerce_types() {
+        let result = object_is(vec![ExpressionResult::Number(1.0), ExpressionResult::String("1".into())]);
+        assert_eq!(result, Ok(ExpressionResult::Boolean(false)));
+    }
+
+    #[test]
+    fn fround_should_round_to_the_nearest_32_bit_float() {
+        assert_eq!(math_fround(vec![ExpressionResult::Number(1.1)]), Ok(ExpressionResult::Number(1.100000023841858)));
+        assert_eq!(math_fround(vec![ExpressionResult::Number(5.5)]), Ok(ExpressionResult::Number(5.5)));
+        assert_eq!(math_fround(vec![ExpressionResult::Number(1e40)]), Ok(ExpressionResult::Number(f64::INFINITY)));
+    }
+
+    #[test]
+    fn number_should_convert_strings_and_give_nan_otherwise() {
+        assert_eq!(number(vec![ExpressionResult::String("42".into())]), Ok(ExpressionResult::Number(42.0)));
+        assert_eq!(number(vec![ExpressionResult::Boolean(true)]), Ok(ExpressionResult::Number(1.0)));
+        assert_eq!(number(vec![]), Ok(ExpressionResult::Number(0.0)));
+        let not_a_number = number(vec![ExpressionResult::String("abc".into())]);
+        assert!(matches!(not_a_number, Ok(ExpressionResult::Number(n)) if n.is_nan()));
+    }
+
+    #[test]
+    fn number_should_read_strings_the_way_js_does() {
+        let converted = |value: &str| match number(vec![ExpressionResult::String(value.into())]) {
+            Ok(ExpressionResult::Number(result)) => result,
+            other => panic!("expected a number, got {:?}", other),
+        };
+        assert_eq!(converted(""), 0.0);
+        assert_eq!(converted("   "), 0.0);
+        assert_eq!(converted(" 42 "), 42.0);
+        assert_eq!(converted("-1.5e2"), -150.0);
+        assert_eq!(converted("Infinity"), f64::INFINITY);
+        assert_eq!(converted("-Infinity"), f64::NEG_INFINITY);
+        assert!(converted("inf").is_nan());
+        assert!(converted("infinity").is_nan());
+        assert!(converted("NaN").is_nan());
+        assert!(converted("--5").is_nan());
+        assert!(converted("4 2").is_nan());
+    }
+
+    #[test]
+    fn is_nan_and_is_finite_should_convert_their_argument_to_a_number() {
+        let check = |function: NativeFn, value: ExpressionResult| match function(vec![value]) {
+            Ok(ExpressionResult::Boolean(result)) => result,
+            other => panic!("expected a boolean, got {:?}", other),
+        };
+        assert!(!check(is_nan, ExpressionResult::Number(5.0)));
+        assert!(!check(is_nan, ExpressionResult::String("5".into())));
+        assert!(check(is_nan, ExpressionResult::String("abc".into())));
+        assert!(check(is_nan, ExpressionResult::Undefined));
+        assert!(check(is_finite, ExpressionResult::String("1e3".into())));
+        assert!(!check(is_finite, ExpressionResult::Number(f64::INFINITY)));
+        assert!(!check(is_finite, ExpressionResult::String("abc".into())));
+        assert!(!check(is_nan, ExpressionResult::String(" 42 ".into())));
+        assert!(check(is_finite, ExpressionResult::String("".into())));
+        assert!(check(is_nan, ExpressionResult::String("inf".into())));
+        assert!(!check(is_finite, ExpressionResult::String("-Infinity".into())));
+    }
+
+    #[test]
+    fn boolean_should_convert_by_truthiness() {
+        assert_eq!(boolean(vec![ExpressionResult::Number(0.0)]), Ok(ExpressionResult::Boolean(false)));
+        assert_eq!(boolean(vec![ExpressionResult::String("x".into())]), Ok(ExpressionResult::Boolean(true)));
+        assert_eq!(boolean(vec![]), Ok(ExpressionResult::Boolean(false)));
+    }
+
+    #[test]
+    fn string_should_convert_every_kind_of_value() {
+        let converted = |value: ExpressionResult| match string(vec![value]) {
+            Ok(ExpressionResult::String(result)) => result,
+            other => panic!("expected a string, got {:?}", other),
+        };
+        assert_eq!(converted(ExpressionResult::Number(42.0)), "42");
+        assert_eq!(converted(ExpressionResult::Number(f64::INFINITY)), "Infinity");
+        assert_eq!(converted(ExpressionResult::String("text".into())), "text");
+        assert_eq!(converted(ExpressionResult::Boolean(true)), "true");
+        assert_eq!(converted(numbers(vec![1.0, 2.0])), "1,2");
+        assert_eq!(converted(object(vec![("a", ExpressionResult::Number(1.0))])), "[object Object]");
+        assert_eq!(
+            converted(ExpressionResult::NativeFunction(NativeFunction::new("range", range))),
+            "function range() { [native code] }"
+        );
+        assert_eq!(
+            converted(ExpressionResult::Function("f".into(), Function::new(vec![], Block::new(vec![])))),
+            "function f() {\n}"
+        );
+        assert_eq!(converted(ExpressionResult::Null), "null");
+        assert_eq!(converted(ExpressionResult::Undefined), "undefined");
+        assert_eq!(string(vec![]), Ok(ExpressionResult::String(String::new())));
+    }
+}
@@ -1,3 +1,5 @@
+use std::cmp::Ordering;
+
 use crate::ast::{ExpressionResult, Operator};
 use crate::environment::Environment;
 use crate::interpreter::errors::{InterpreterError, InterpreterErrorKind};
@@ -27,16 +29,7 @@ impl BinaryOperator for AddOperator {
             let new_string = left.coerce_to_string() + &right.coerce_to_string();
             Ok(ExpressionResult::String(new_string))
         } else {
-            let left_num = left.coerce_to_number();
-            let right_num = right.coerce_to_number();
-            if let (Ok(l), Ok(r)) = (left_num, right_num) {
-                Ok(ExpressionResult::Number(l + r))
-            } else {
-                Err(InterpreterError {
-                    kind: InterpreterErrorKind::NaN,
-                }
-                .to_string())
-            }
+            Ok(ExpressionResult::Number(left.to_number() + right.to_number()))
         }
     }
 }
@@ -49,14 +42,8 @@ impl BinaryOperator for SubtractOperator {
         right: ExpressionResult,
         _env: &mut Environment,
     ) -> Result<ExpressionResult, String> {
-        if let (Ok(l), Ok(r)) = (left.coerce_to_number(), right.coerce_to_number()) {
-            Ok(ExpressionResult::Number(l - r))
-        } else {
-            Err(InterpreterError {
-                kind: InterpreterErrorKind::NaN,
-            }
-            .to_string())
-        }
+        let (l, r) = (left.to_number(), right.to_number());
+        Ok(ExpressionResult::Number(l - r))
     }
 }
 
@@ -68,18 +55,12 @@ impl BinaryOperator for MultiplyOperator {
         right: ExpressionResult,
         _env: &mut Environment,
     ) -> Result<ExpressionResult, String> {
-        if let (Ok(l), Ok(r)) = (left.coerce_to_number(), right.coerce_to_number()) {
-            Ok(ExpressionResult::Number(l * r))
-        } else {
-            Err(InterpreterError {
-                kind: InterpreterErrorKind::NaN,
-            }
-            .to_string())
-        }
+        let (l, r) = (left.to_number(), right.to_number());
+        Ok(ExpressionResult::Number(l * r))
     }
 }
 
-/// Division needs special handling for division by zero
+/// Division by zero follows IEEE 754 like JS, so 1 / 0 is Infinity, -1 / 0 is -Infinity and 0 / 0 is NaN
 pub struct DivideOperator;
 impl BinaryOperator for DivideOperator {
     fn apply(
@@ -88,21 +69,7 @@ impl BinaryOperator for DivideOperator {
         right: ExpressionResult,
         _env: &mut Environment,
     ) -> Result<ExpressionResult, String> {
-        if let (Ok(l), Ok(r)) = (left.coerce_to_number(), right.coerce_to_number()) {
-            if r.abs() < f64::EPSILON {
-                Err(InterpreterError {
-                    kind: InterpreterErrorKind::DivisionByZero
-                }
-                .to_string())
-            } else {
-                Ok(ExpressionResult::Number(l / r))
-            }
-        } else {
-            Err(InterpreterError {
-                kind: InterpreterErrorKind::NaN,
-            }
-            .to_string())
-        }
+        Ok(ExpressionResult::Number(left.to_number() / right.to_number()))
     }
 }
 
@@ -114,14 +81,8 @@ impl BinaryOperator for ModuloOperator {
         right: ExpressionResult,
         _env: &mut Environment,
     ) -> Result<ExpressionResult, String> {
-        if let (Ok(l), Ok(r)) = (left.coerce_to_number(), right.coerce_to_number()) {
-            Ok(ExpressionResult::Number(l % r))
-        } else {
-            Err(InterpreterError {
-                kind: InterpreterErrorKind::NaN,
-            }
-            .to_string())
-        }
+        let (l, r) = (left.to_number(), right.to_number());
+        Ok(ExpressionResult::Number(l % r))
     }
 }
 
@@ -133,14 +94,8 @@ impl BinaryOperator for ExponentiationOperator {
         right: ExpressionResult,
         _env: &mut Environment,
     ) -> Result<ExpressionResult, String> {
-        if let (Ok(l), Ok(r)) = (left.coerce_to_number(), right.coerce_to_number()) {
-            Ok(ExpressionResult::Number(l.powf(r)))
-        } else {
-            Err(InterpreterError {
-                kind: InterpreterErrorKind::NaN,
-            }
-            .to_string())
-        }
+        let (l, r) = (left.to_number(), right.to_number());
+        Ok(ExpressionResult::Number(l.powf(r)))
     }
 }
 
@@ -152,6 +107,16 @@ impl BinaryOperator for EqualOperator {
         right: ExpressionResult,
         _env: &mut Environment,
     ) -> Result<ExpressionResult, String> {
+        // NaN is not equal to anything, itself included
+        if left.is_nan() || right.is_nan() {
+            return Ok(ExpressionResult::Boolean(false));
+        }
+
+        // null and undefined are only loosely equal to each other, so 0 == null is false even though null is falsy
+        if left.is_nullish() || right.is_nullish() {
+            return Ok(ExpressionResult::Boolean(left.is_nullish() && right.is_nullish()));
+        }
+
         if matches!(left, ExpressionResult::Boolean(_))
             || matches!(right, ExpressionResult::Boolean(_))
         {
@@ -181,6 +146,12 @@ impl BinaryOperator for EqualOperator {
     }
 }
 
+/// Two strings compare lexicographically rather than as numbers, so "10" < "9".
+/// JS compares strings by UTF-16 code units, which orders some characters differently to comparing UTF-8 bytes
+fn compare_strings(left: &str, right: &str) -> Ordering {
+    left.encode_utf16().cmp(right.encode_utf16())
+}
+
 pub struct LessThanOperator;
 impl BinaryOperator for LessThanOperator {
     fn apply(
@@ -189,6 +160,9 @@ impl BinaryOperator for LessThanOperator {
         right: ExpressionResult,
         _env: &mut Environment,
     ) -> Result<ExpressionResult, String> {
+        if let (ExpressionResult::String(l), ExpressionResult::String(r)) = (&left, &right) {
+            return Ok(ExpressionResult::Boolean(compare_strings(l, r) < Ordering::Equal));
+        }
         if let (Ok(l), Ok(r)) = (left.coerce_to_number(), right.coerce_to_number()) {
             Ok(ExpressionResult::Boolean(l < r))
         } else {
@@ -205,6 +179,9 @@ impl BinaryOperator for GreaterThanOperator {
         right: ExpressionResult,
         _env: &mut Environment,
     ) -> Result<ExpressionResult, String> {
+        if let (ExpressionResult::String(l), ExpressionResult::String(r)) = (&left, &right) {
+            return Ok(ExpressionResult::Boolean(compare_strings(l, r) > Ordering::Equal));
+        }
         if let (Ok(l), Ok(r)) = (left.coerce_to_number(), right.coerce_to_number()) {
             Ok(ExpressionResult::Boolean(l > r))
         } else {
@@ -213,6 +190,44 @@ impl BinaryOperator for GreaterThanOperator {
     }
 }
 
+pub struct LessThanOrEqualOperator;
+impl BinaryOperator for LessThanOrEqualOperator {
+    fn apply(
+        &self,
+        left: ExpressionResult,
+        right: ExpressionResult,
+        _env: &mut Environment,
+    ) -> Result<ExpressionResult, String> {
+        if let (ExpressionResult::String(l), ExpressionResult::String(r)) = (&left, &right) {
+            return Ok(ExpressionResult::Boolean(compare_strings(l, r) <= Ordering::Equal));
+        }
+        if let (Ok(l), Ok(r)) = (left.coerce_to_number(), right.coerce_to_number()) {
+            Ok(ExpressionResult::Boolean(l <= r))
+        } else {
+            Ok(ExpressionResult::Boolean(false))
+        }
+    }
+}
+
+pub struct GreaterThanOrEqualOperator;
+impl BinaryOperator for GreaterThanOrEqualOperator {
+    fn apply(
+        &self,
+        left: ExpressionResult,
+        right: ExpressionResult,
+        _env: &mut Environment,
+    ) -> Result<ExpressionResult, String> {
+        if let (ExpressionResult::String(l), ExpressionResult::String(r)) = (&left, &right) {
+            return Ok(ExpressionResult::Boolean(compare_strings(l, r) >= Ordering::Equal));
+        }
+        if let (Ok(l), Ok(r)) = (left.coerce_to_number(), right.coerce_to_number()) {
+            Ok(ExpressionResult::Boolean(l >= r))
+        } else {
+            Ok(ExpressionResult::Boolean(false))
+        }
+    }
+}
+
 pub struct AndOperator;
 impl BinaryOperator for AndOperator {
     fn apply(
@@ -241,6 +256,61 @@ impl BinaryOperator for OrOperator {
     }
 }
 
+/// left ?? right is the left side unless it is null or undefined.
+/// Unlike ||, falsy values such as 0 and "" are kept
+pub struct NullishCoalescingOperator;
+impl BinaryOperator for NullishCoalescingOperator {
+    fn apply(
+        &self,
+        left: ExpressionResult,
+        right: ExpressionResult,
+        _env: &mut Environment,
+    ) -> Result<ExpressionResult, String> {
+        if left.is_nullish() {
+            Ok(right)
+        } else {
+            Ok(left)
+        }
+    }
+}
+
+/// key in object is whether the object has the key, with the left side converted to a string key.
+/// Arrays have their indices and length as keys
+pub struct InOperator;
+impl BinaryOperator for InOperator {
+    fn apply(
+        &self,
+        left: ExpressionResult,
+        right: ExpressionResult,
+        _env: &mut Environment,
+    ) -> Result<ExpressionResult, String> {
+        let key = left.coerce_to_string();
+        match right {
+            ExpressionResult::Object(object) => Ok(ExpressionResult::Boolean(object.get(&key).is_some())),
+            ExpressionResult::Array(elements) => {
+                let is_index = key.parse::<usize>().is_ok_and(|index| index < elements.len() && index.to_string() == key);
+                Ok(ExpressionResult::Boolean(is_index || key == "length"))
+            }
+            _ => Err(InterpreterError {
+                kind: InterpreterErrorKind::TypeError(format!(
+                    "Cannot use 'in' operator to search for '{}' in {}",
+                    key, right
+                )),
+            }
+            .to_string()),
+        }
+    }
+}
+
+/// left === right, which never converts either side, so 1 === "1" is false.
+/// NaN is not equal to itself, while 0 and -0 are equal
+pub fn strictly_equal(left: &ExpressionResult, right: &ExpressionResult) -> bool {
+    match (left, right) {
+        (ExpressionResult::Number(l), ExpressionResult::Number(r)) => l == r,
+        _ => left == right,
+    }
+}
+
 pub fn get_operator_strategy(operator: Operator) -> Box<dyn BinaryOperator> {
     match operator {
         Operator::Add => Box::new(AddOperator),
@@ -251,15 +321,20 @@ pub fn get_operator_strategy(operator: Operator) -> Box<dyn BinaryOperator> {
         Operator::Equal => Box::new(EqualOperator),
         Operator::LessThan => Box::new(LessThanOperator),
         Operator::GreaterThan => Box::new(GreaterThanOperator),
+        Operator::LessThanOrEqual => Box::new(LessThanOrEqualOperator),
+        Operator::GreaterThanOrEqual => Box::new(GreaterThanOrEqualOperator),
         Operator::And => Box::new(AndOperator),
         Operator::Or => Box::new(OrOperator),
         Operator::Exponentiation => Box::new(ExponentiationOperator),
+        Operator::NullishCoalescing => Box::new(NullishCoalescingOperator),
+        Operator::In => Box::new(InOperator),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::object::Object;
 
     #[test]
     fn add_operator_should_concatenate_strings() {
@@ -289,18 +364,163 @@ mod tests {
     }
 
     #[test]
-    fn divide_operator_should_handle_division_by_zero() {     
-        let left = ExpressionResult::Number(10.0);
+    fn divide_operator_should_give_infinity_for_division_by_zero() {
+        let operator = DivideOperator;
+        let positive = operator.apply(ExpressionResult::Number(10.0), ExpressionResult::Number(0.0), &mut Environment::new());
+        let negative = operator.apply(ExpressionResult::Number(-10.0), ExpressionResult::Number(0.0), &mut Environment::new());
+        assert_eq!(positive, Ok(ExpressionResult::Number(f64::INFINITY)));
+        assert_eq!(negative, Ok(ExpressionResult::Number(f64::NEG_INFINITY)));
+    }
+
+    #[test]
+    fn divide_operator_should_return_nan_for_zero_by_zero() {
+        let left = ExpressionResult::Number(0.0);
         let right = ExpressionResult::Number(0.0);
         let operator = DivideOperator;
-        let result = operator.apply(left, right, &mut Environment::new());
-        assert!(result.is_err());
-        assert_eq!(
-            result.err().unwrap(),
-            InterpreterError {
-                kind: InterpreterErrorKind::DivisionByZero
+        let result = operator.apply(left, right, &mut Environment::new()).unwrap();
+        assert!(result.is_nan());
+    }
+
+    #[test]
+    fn equal_operator_should_not_equal_nan_to_itself() {
+        let left = ExpressionResult::Number(f64::NAN);
+        let right = ExpressionResult::Number(f64::NAN);
+        let operator = EqualOperator;
+        let result = operator.apply(left, right, &mut Environment::new()).unwrap();
+        assert_eq!(result, ExpressionResult::Boolean(false));
+    }
+
+    #[test]
+    fn equal_operator_should_not_equal_nan_to_false() {
+        let left = ExpressionResult::Number(f64::NAN);
+        let right = ExpressionResult::Boolean(false);
+        let operator = EqualOperator;
+        let result = operator.apply(left, right, &mut Environment::new()).unwrap();
+        assert_eq!(result, ExpressionResult::Boolean(false));
+    }
+
+    #[test]
+    fn nullish_coalescing_operator_should_keep_falsy_values() {
+        let left = ExpressionResult::Number(0.0);
+        let right = ExpressionResult::Number(5.0);
+        let operator = NullishCoalescingOperator;
+        let result = operator.apply(left, right, &mut Environment::new()).unwrap();
+        assert_eq!(result, ExpressionResult::Number(0.0));
+    }
+
+    #[test]
+    fn nullish_coalescing_operator_should_replace_null() {
+        let left = ExpressionResult::Null;
+        let right = ExpressionResult::Number(5.0);
+        let operator = NullishCoalescingOperator;
+        let result = operator.apply(left, right, &mut Environment::new()).unwrap();
+        assert_eq!(result, ExpressionResult::Number(5.0));
+    }
+
+    fn compare(operator: &dyn BinaryOperator, left: &str, right: &str) -> ExpressionResult {
+        operator
+            .apply(
+                ExpressionResult::String(left.into()),
+                ExpressionResult::String(right.into()),
+                &mut Environment::new(),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn less_than_operator_should_compare_strings_lexicographically() {
+        assert_eq!(compare(&LessThanOperator, "a", "b"), ExpressionResult::Boolean(true));
+        assert_eq!(compare(&LessThanOperator, "b", "a"), ExpressionResult::Boolean(false));
+        assert_eq!(compare(&LessThanOperator, "10", "9"), ExpressionResult::Boolean(true));
+    }
+
+    #[test]
+    fn greater_than_operator_should_compare_strings_lexicographically() {
+        assert_eq!(compare(&GreaterThanOperator, "banana", "apple"), ExpressionResult::Boolean(true));
+        assert_eq!(compare(&GreaterThanOperator, "9", "10"), ExpressionResult::Boolean(true));
+        assert_eq!(compare(&GreaterThanOperator, "a", "a"), ExpressionResult::Boolean(false));
+    }
+
+    #[test]
+    fn less_than_operator_should_compare_mixed_string_and_number_numerically() {
+        let left = ExpressionResult::String("10".into());
+        let right = ExpressionResult::Number(9.0);
+        let result = LessThanOperator.apply(left, right, &mut Environment::new()).unwrap();
+        assert_eq!(result, ExpressionResult::Boolean(false));
+    }
+
+    #[test]
+    fn less_than_or_equal_operator_should_include_equal_numbers() {
+        let operator = LessThanOrEqualOperator;
+        let equal = operator.apply(ExpressionResult::Number(2.0), ExpressionResult::Number(2.0), &mut Environment::new());
+        let greater = operator.apply(ExpressionResult::Number(3.0), ExpressionResult::Number(2.0), &mut Environment::new());
+        assert_eq!(equal, Ok(ExpressionResult::Boolean(true)));
+        assert_eq!(greater, Ok(ExpressionResult::Boolean(false)));
+    }
+
+    #[test]
+    fn greater_than_or_equal_operator_should_be_false_for_nan() {
+        let operator = GreaterThanOrEqualOperator;
+        let result = operator.apply(ExpressionResult::Number(f64::NAN), ExpressionResult::Number(f64::NAN), &mut Environment::new());
+        assert_eq!(result, Ok(ExpressionResult::Boolean(false)));
+    }
+
+    #[test]
+    fn greater_than_or_equal_operator_should_compare_strings_lexicographically() {
+        assert_eq!(compare(&GreaterThanOrEqualOperator, "b", "b"), ExpressionResult::Boolean(true));
+        assert_eq!(compare(&GreaterThanOrEqualOperator, "10", "9"), ExpressionResult::Boolean(false));
+    }
+
+    #[test]
+    fn in_operator_should_check_object_keys() {
+        let mut object = Object::new();
+        object.set("a".into(), ExpressionResult::Undefined);
+        let present = InOperator.apply(ExpressionResult::String("a".into()), ExpressionResult::Object(object.clone()), &mut Environment::new());
+        let absent = InOperator.apply(ExpressionResult::String("b".into()), ExpressionResult::Object(object), &mut Environment::new());
+        assert_eq!(present, Ok(ExpressionResult::Boolean(true)));
+        assert_eq!(absent, Ok(ExpressionResult::Boolean(false)));
+    }
+
+    #[test]
+    fn in_operator_should_reject_non_objects() {
+        let result = InOperator.apply(ExpressionResult::String("a".into()), ExpressionResult::String("abc".into()), &mut Environment::new());
+        assert_eq!(result, Err("Uncaught TypeError: Cannot use 'in' operator to search for 'a' in abc".to_string()));
+    }
+
+    /// The null and undefined rows of the abstract equality table, where null and undefined only equal each other
+    mod null_and_undefined_equality {
+        use super::*;
+
+        fn loosely_equal(left: ExpressionResult, right: ExpressionResult) -> bool {
+            EqualOperator.apply(left, right, &mut Environment::new()) == Ok(ExpressionResult::Boolean(true))
+        }
+
+        #[test]
+        fn null_and_undefined_should_equal_each_other_and_themselves() {
+            assert!(loosely_equal(ExpressionResult::Null, ExpressionResult::Undefined));
+            assert!(loosely_equal(ExpressionResult::Undefined, ExpressionResult::Null));
+            assert!(loosely_equal(ExpressionResult::Null, ExpressionResult::Null));
+            assert!(loosely_equal(ExpressionResult::Undefined, ExpressionResult::Undefined));
+        }
+
+        #[test]
+        fn null_and_undefined_should_not_equal_falsy_values() {
+            for nullish in [ExpressionResult::Null, ExpressionResult::Undefined] {
+                assert!(!loosely_equal(nullish.clone(), ExpressionResult::Number(0.0)));
+                assert!(!loosely_equal(ExpressionResult::Number(0.0), nullish.clone()));
+                assert!(!loosely_equal(nullish.clone(), ExpressionResult::String("".into())));
+                assert!(!loosely_equal(nullish.clone(), ExpressionResult::Boolean(false)));
+                assert!(!loosely_equal(nullish.clone(), ExpressionResult::Number(f64::NAN)));
+                assert!(!loosely_equal(nullish, ExpressionResult::Array(vec![])));
             }
-            .to_string()
-        );
+        }
+
+        #[test]
+        fn null_should_coerce_to_zero_and_undefined_to_nan() {
+            assert_eq!(ExpressionResult::Null.coerce_to_number(), Ok(0.0));
+            assert!(ExpressionResult::Undefined.coerce_to_number().unwrap().is_nan());
+            let sum = AddOperator.apply(ExpressionResult::Undefined, ExpressionResult::Number(1.0), &mut Environment::new());
+            assert!(sum.unwrap().is_nan());
+        }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,121 @@
+use std::{
+    io::Write,
+    sync::{Arc, Mutex},
+};
+
+use crate::{ast::ExpressionResult, interpreter::json::quote};
+
+/// Where the results of expression statements are printed.
+///
+/// Child environments clone the output, so they all share the same writer.
+/// The writer has to be `Send`, as `run` evaluates on a thread of its own.
+#[derive(Clone)]
+pub struct Output {
+    writer: Arc<Mutex<dyn Write + Send>>,
+    annotate_types: bool,
+}
+
+impl Output {
+    pub fn stdout() -> Self {
+        Self::new(PrintedStdout)
+    }
+
+    pub fn new(writer: impl Write + Send + 'static) -> Self {
+        Output {
+            writer: Arc::new(Mutex::new(writer)),
+            annotate_types: false,
+        }
+    }
+
+    /// Prints each result followed by its type, such as `5 : number`
+    pub fn with_type_annotations(mut self, annotate_types: bool) -> Self {
+        self.annotate_types = annotate_types;
+        self
+    }
+
+    pub fn print_result(&self, value: &ExpressionResult) {
+        let line = if self.annotate_types {
+            let shown = match value {
+                ExpressionResult::String(string) => quote(string),
+                _ => value.to_string(),
+            };
+            format!("{} : {}", shown, value.type_of())
+        } else {
+            value.to_string()
+        };
+        // a run that panicked while printing poisons the lock, but the writer itself is still fine to use
+        let mut writer = self.writer.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        // a closed output shouldn't stop the script from running
+        let _ = writeln!(writer, "{}", line);
+    }
+
+}
+
+/// Writes to stdout through `print!`, which a test harness captures, unlike writing to `std::io::stdout()` directly
+struct PrintedStdout;
+
+impl Write for PrintedStdout {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        print!("{}", String::from_utf8_lossy(buf));
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        std::io::stdout().flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{environment::Environment, interpreter::errors::RunError, run};
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedBuffer {
+        fn contents(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    #[test]
+    fn it_should_print_only_the_value_by_default() {
+        let buffer = SharedBuffer::default();
+        let output = Output::new(buffer.clone());
+        output.print_result(&ExpressionResult::String("hi".to_string()));
+        assert_eq!(buffer.contents(), "hi\n");
+    }
+
+    #[test]
+    fn it_should_print_the_type_when_annotating() {
+        let buffer = SharedBuffer::default();
+        let mut env = Environment::new();
+        env.output = Output::new(buffer.clone()).with_type_annotations(true);
+        run("5; \"hi\";", &mut env).unwrap();
+        assert_eq!(buffer.contents(), "5 : number\n\"hi\" : string\n");
+    }
+
+    #[test]
+    fn it_should_stop_printing_at_an_uncaught_error_and_leave_it_to_the_caller() {
+        let buffer = SharedBuffer::default();
+        let mut env = Environment::new();
+        env.output = Output::new(buffer.clone());
+        let result = run("1; x; 2;", &mut env);
+        assert_eq!(
+            result,
+            Err(RunError::Runtime("Uncaught ReferenceError: x is not defined".to_string()))
+        );
+        assert_eq!(buffer.contents(), "1\n");
+    }
+}
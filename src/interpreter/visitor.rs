@@ -1,17 +1,22 @@
-use crate::ast::{Expression, ExpressionResult, Operator, PrefixOperator, Statement, Node};
+use crate::ast::{Completion, Expression, ExpressionResult, Operator, Pattern, PostfixOperator, PrefixOperator, Statement, Node, Thrown};
 use crate::environment::Environment;
+use crate::function::Function;
+use crate::lexer::Token;
+use crate::object::Object;
 use crate::interpreter::{
     errors::{InterpreterError, InterpreterErrorKind, SyntaxErrorKind},
-    operators::get_operator_strategy,
+    methods::{call_method, get_index, get_property, is_mutating_method, set_index},
+    interpreter::{declare_functions, run_statements},
+    operators::{get_operator_strategy, strictly_equal},
 };
 
 /// Trait for visiting AST nodes.
 ///
-/// Statements return `Option<ExpressionResult>` to allow early returns,
-/// while expressions return a `Result<ExpressionResult, String>` to surface runtime errors.
+/// Statements return `Option<Completion>` so a return, break, continue or throw can skip the statements after them,
+/// while expressions return a `Result<ExpressionResult, Thrown>` to surface runtime errors and thrown values.
 pub trait NodeVisitor {
-    fn visit_statement(&mut self, statement: &Statement) -> Option<ExpressionResult>;
-    fn visit_expression(&mut self, expression: &Expression) -> Result<ExpressionResult, String>;
+    fn visit_statement(&mut self, statement: &Statement) -> Option<Completion>;
+    fn visit_expression(&mut self, expression: &Expression) -> Result<ExpressionResult, Thrown>;
 }
 
 pub struct Evaluator<'a> {
@@ -28,113 +33,324 @@ impl<'a> Evaluator<'a> {
         left_hand: &Expression,
         operator: &Operator,
         right_hand: &Expression,
-    ) -> Result<ExpressionResult, String> {
-        let left_result = left_hand.accept(self);
-        if let Ok(left_value) = left_result {
-            // short circuit behavior for logical operators
-            if *operator == Operator::And && !left_value.coerce_to_bool() {
-                return Ok(ExpressionResult::Boolean(false));
-            }
-            if *operator == Operator::Or && left_value.coerce_to_bool() {
-                return Ok(ExpressionResult::Boolean(true));
-            }
-            let right_value = right_hand.accept(self)?;
-            let strategy = get_operator_strategy(operator.clone());
-            strategy.apply(left_value, right_value, self.env)
-        } else {
-            Err(InterpreterError {
-                kind: InterpreterErrorKind::SyntaxError(None),
-            }
-            .to_string())
+    ) -> Result<ExpressionResult, Thrown> {
+        let left_value = left_hand.accept(self)?;
+        // short circuit behavior for logical operators
+        if *operator == Operator::And && !left_value.coerce_to_bool() {
+            return Ok(ExpressionResult::Boolean(false));
         }
+        if *operator == Operator::Or && left_value.coerce_to_bool() {
+            return Ok(ExpressionResult::Boolean(true));
+        }
+        if *operator == Operator::NullishCoalescing && !left_value.is_nullish() {
+            return Ok(left_value);
+        }
+        let right_value = right_hand.accept(self)?;
+        let strategy = get_operator_strategy(operator.clone());
+        Ok(strategy.apply(left_value, right_value, self.env)?)
     }
 
     fn evaluate_prefix_expression(
         &mut self,
         operator: &PrefixOperator,
         expression: &Expression,
-    ) -> Result<ExpressionResult, String> {
-        let result = expression.accept(self);
-        if let Ok(value) = result {
-            match operator {
-                PrefixOperator::Negative | PrefixOperator::Positive => {
-                    let sign = if *operator == PrefixOperator::Negative {
-                        -1.0
-                    } else {
-                        1.0
-                    };
-                    let coercion = value.coerce_to_number();
-                    if let Ok(number) = coercion {
-                        return Ok(ExpressionResult::Number(sign * number));
-                    } else {
-                        return Err(InterpreterError {
-                            kind: InterpreterErrorKind::NaN,
-                        }
-                        .to_string());
-                    }
+    ) -> Result<ExpressionResult, Thrown> {
+        // typeof is the one place an undeclared name isn't a ReferenceError
+        if let (PrefixOperator::TypeOf, Expression::Identifier(identifier)) = (operator, expression) {
+            if !self.env.has_variable(identifier.clone()) && !self.env.has_function(identifier.clone()) {
+                return Ok(ExpressionResult::String("undefined".to_string()));
+            }
+        }
+        if *operator == PrefixOperator::Delete {
+            return self.delete_property(expression);
+        }
+        let value = expression.accept(self)?;
+        match operator {
+            PrefixOperator::Negative | PrefixOperator::Positive => {
+                let sign = if *operator == PrefixOperator::Negative {
+                    -1.0
+                } else {
+                    1.0
+                };
+                Ok(ExpressionResult::Number(sign * value.to_number()))
+            }
+            PrefixOperator::Not => {
+                let bool = value.coerce_to_bool();
+                Ok(ExpressionResult::Boolean(!bool))
+            }
+            PrefixOperator::TypeOf => Ok(ExpressionResult::String(value.type_of().to_string())),
+            PrefixOperator::Delete => unreachable!("delete never evaluates its operand"),
+            PrefixOperator::Decrement | PrefixOperator::Increment => match expression {
+                Expression::Identifier(_) | Expression::Member(_, _) | Expression::Index(_, _) => {
+                    let (_, new) = self.modify_target_and_return_values(operator, expression)?;
+                    return Ok(ExpressionResult::Number(new));
                 }
-                PrefixOperator::Not => {
-                    let bool = value.coerce_to_bool();
-                    Ok(ExpressionResult::Boolean(!bool))
+                _ => {
+                    return Err(InterpreterError {
+                        kind: InterpreterErrorKind::SyntaxError(Some(
+                            SyntaxErrorKind::InvalidLeftSidePrefix,
+                        )),
+                    }
+                    .to_string().into())
                 }
-                PrefixOperator::Decrement | PrefixOperator::Increment => match expression {
-                    Expression::Identifier(identifier) => {
-                        return self
-                            .modify_variable_and_return_new_value(operator.clone(), identifier.clone());
+            },
+        }
+    }
+
+    /// Removes `object.key` or `object[key]` from an object, which can be nested anywhere inside a variable.
+    /// Like sloppy mode JS, deleting anything else does nothing and every delete gives true.
+    fn delete_property(&mut self, expression: &Expression) -> Result<ExpressionResult, Thrown> {
+        let (Expression::Member(object, _) | Expression::Index(object, _)) = expression else {
+            return Ok(ExpressionResult::Boolean(true));
+        };
+        // the object is found the way an assignment finds what it writes to, before the key is evaluated
+        let path = self.resolve_target(object)?;
+        let key = match expression {
+            Expression::Index(_, index) => index.accept(self)?.coerce_to_string(),
+            Expression::Member(_, key) => key.clone(),
+            _ => unreachable!(),
+        };
+        match self.read_target(&path) {
+            ExpressionResult::Object(_) => {
+                self.write_target(path, |container| {
+                    let mut container = container.clone();
+                    if let ExpressionResult::Object(object) = &mut container {
+                        object.remove(&key);
                     }
-                    _ => {
+                    Ok(container)
+                })?;
+            }
+            value if value.is_nullish() => {
+                return Err(InterpreterError {
+                    kind: InterpreterErrorKind::TypeError("Cannot convert undefined or null to object".into()),
+                }
+                .to_string().into())
+            }
+            _ => {}
+        }
+        Ok(ExpressionResult::Boolean(true))
+    }
+
+    /// Evaluates a list of expressions in order, expanding any spread expression into its elements
+    fn evaluate_expression_list(
+        &mut self,
+        expressions: &[Expression],
+    ) -> Result<Vec<ExpressionResult>, Thrown> {
+        let mut values = vec![];
+        for expression in expressions {
+            match expression {
+                Expression::Spread(inner) => match inner.accept(self)? {
+                    ExpressionResult::Array(elements) => values.extend(elements),
+                    value => {
                         return Err(InterpreterError {
-                            kind: InterpreterErrorKind::SyntaxError(Some(
-                                SyntaxErrorKind::InvalidLeftSidePrefix,
-                            )),
+                            kind: InterpreterErrorKind::TypeError(format!("{} is not iterable", value)),
                         }
-                        .to_string())
+                        .to_string().into())
                     }
                 },
+                _ => values.push(expression.accept(self)?),
             }
-        } else {
-            return Err(InterpreterError {
-                kind: InterpreterErrorKind::SyntaxError(None),
+        }
+        Ok(values)
+    }
+
+    /// Calls a function value, as Expression::Call does once it has evaluated the callee and arguments
+    fn call_value(&mut self, callee: ExpressionResult, arguments: Vec<ExpressionResult>) -> Result<ExpressionResult, Thrown> {
+        match callee {
+            ExpressionResult::NativeFunction(native) => Ok(native.call(arguments)?),
+            ExpressionResult::Function(_, function) => function.call(arguments, self.env),
+            value => Err(InterpreterError {
+                kind: InterpreterErrorKind::TypeError(format!("{} is not a function", value)),
             }
-            .to_string());
+            .to_string().into()),
         }
     }
 
-    fn modify_variable_and_return_new_value(
+    /// Runs `array.map(callback)` and `array.filter(callback)`, which call back into the script for each element.
+    /// The callback gets the element, its index and the array, although script functions only get as many
+    /// of those as they have parameters, since calls have to match the parameter count here
+    fn call_array_callback_method(
         &mut self,
-        operator: PrefixOperator,
-        identifier: String,
-    ) -> Result<ExpressionResult, String> {
-        let stored_value = self.env.get_variable(&identifier);
-        match stored_value {
-            Some(previous_value) => {
-                if let Ok(previous_value_as_number) = previous_value.coerce_to_number() {
-                    let new = if operator == PrefixOperator::Decrement {
-                        ExpressionResult::Number(previous_value_as_number - 1.0)
-                    } else {
-                        ExpressionResult::Number(previous_value_as_number + 1.0)
-                    };
-                    self.env.set_variable(identifier.clone(), new.clone());
-                    return Ok(new);
+        elements: Vec<ExpressionResult>,
+        method: &str,
+        arguments: Vec<ExpressionResult>,
+    ) -> Result<ExpressionResult, Thrown> {
+        let callback = arguments.into_iter().next().unwrap_or(ExpressionResult::Undefined);
+        if !matches!(callback, ExpressionResult::NativeFunction(_) | ExpressionResult::Function(_, _)) {
+            return Err(InterpreterError {
+                kind: InterpreterErrorKind::TypeError(format!("{} is not a function", callback)),
+            }
+            .to_string().into());
+        }
+        let mut results = vec![];
+        for (index, element) in elements.iter().enumerate() {
+            let mut callback_arguments = vec![
+                element.clone(),
+                ExpressionResult::Number(index as f64),
+                ExpressionResult::Array(elements.clone()),
+            ];
+            if let ExpressionResult::Function(_, function) = &callback {
+                callback_arguments.truncate(function.parameter_count());
+            }
+            let result = self.call_value(callback.clone(), callback_arguments)?;
+            if method == "map" {
+                results.push(result);
+            } else if result.coerce_to_bool() {
+                results.push(element.clone());
+            }
+        }
+        Ok(ExpressionResult::Array(results))
+    }
+
+    /// Defines every identifier in a destructuring pattern, taking each one's value from the matching
+    /// position or property of the value, so `let [[a], { b }] = [[1], { b: 2 }]` defines a as 1 and b as 2
+    fn bind_pattern(&mut self, pattern: &Pattern, value: ExpressionResult) -> Result<(), Thrown> {
+        match pattern {
+            Pattern::Identifier(identifier) => {
+                self.env.define_variable(identifier.clone(), value);
+            }
+            Pattern::Array(elements) => {
+                if !matches!(value, ExpressionResult::Array(_) | ExpressionResult::String(_)) {
+                    return Err(InterpreterError {
+                        kind: InterpreterErrorKind::TypeError(format!("{} is not iterable", value)),
+                    }
+                    .to_string().into());
                 }
-                return Err(InterpreterError {
-                    kind: InterpreterErrorKind::NaN,
+                for (index, element) in elements.iter().enumerate() {
+                    let element_value = get_index(&value, &ExpressionResult::Number(index as f64));
+                    self.bind_pattern(element, element_value)?;
                 }
-                .to_string());
             }
-            None => {
+            Pattern::Object(properties) => {
+                if matches!(value, ExpressionResult::Null | ExpressionResult::Undefined) {
+                    return Err(InterpreterError {
+                        kind: InterpreterErrorKind::TypeError(format!("Cannot destructure '{}' as it is {}.", value, value)),
+                    }
+                    .to_string().into());
+                }
+                for (key, property) in properties {
+                    self.bind_pattern(property, get_property(&value, key))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Increments or decrements a variable, property or element, returning its value before and after as numbers
+    fn modify_target_and_return_values(
+        &mut self,
+        operator: &PrefixOperator,
+        target: &Expression,
+    ) -> Result<(f64, f64), Thrown> {
+        let step = if *operator == PrefixOperator::Decrement { -1.0 } else { 1.0 };
+        let (previous, new) = self.update_target(target, |value| {
+            Ok(ExpressionResult::Number(value.to_number() + step))
+        })?;
+        Ok((previous.to_number(), new.to_number()))
+    }
+
+    /// Replaces a variable, or a property or element nested anywhere inside one, with `update` applied to it.
+    /// Returns the value before and after the update.
+    fn update_target(
+        &mut self,
+        target: &Expression,
+        update: impl FnOnce(&ExpressionResult) -> Result<ExpressionResult, Thrown>,
+    ) -> Result<(ExpressionResult, ExpressionResult), Thrown> {
+        let path = self.resolve_target(target)?;
+        self.write_target(path, update)
+    }
+
+    /// Finds where an assignment to a variable, property or element writes, evaluating index expressions
+    /// once, left to right, so the value can be worked out before anything is written
+    fn resolve_target<'e>(&mut self, target: &'e Expression) -> Result<TargetPath<'e>, Thrown> {
+        let mut accessors = vec![];
+        let mut root = target;
+        while let Expression::Member(object, _) | Expression::Index(object, _) = root {
+            accessors.push(root);
+            root = object;
+        }
+        let detached_root = match root {
+            Expression::Identifier(identifier) if self.env.has_variable(identifier.clone()) => None,
+            Expression::Identifier(identifier) => {
                 return Err(InterpreterError {
                     kind: InterpreterErrorKind::ReferenceError(identifier.clone()),
                 }
-                .to_string());
+                .to_string().into())
+            }
+            // a container that isn't held in a variable, like f().count, is updated and then thrown away
+            _ => Some(root.accept(self)?),
+        };
+        let mut keys = vec![];
+        for accessor in accessors.iter().rev() {
+            match accessor {
+                Expression::Member(_, key) => keys.push(ExpressionResult::String(key.clone())),
+                Expression::Index(_, index) => keys.push(index.accept(self)?),
+                _ => unreachable!(),
             }
         }
+        Ok(TargetPath { root, detached_root, keys })
+    }
+
+    /// Reads the value a resolved target points at, without writing anything
+    fn read_target(&self, path: &TargetPath) -> ExpressionResult {
+        path.keys
+            .iter()
+            .fold(self.target_root(path), |container, key| get_index(&container, key))
+    }
+
+    fn target_root(&self, path: &TargetPath) -> ExpressionResult {
+        match (&path.detached_root, path.root) {
+            (Some(value), _) => value.clone(),
+            (None, Expression::Identifier(identifier)) => {
+                self.env.get_variable(identifier).unwrap_or(ExpressionResult::Undefined)
+            }
+            (None, _) => unreachable!("only variables are read when written"),
+        }
+    }
+
+    /// Applies `update` to the value a resolved target points at.  Values are not references, so each
+    /// container on the way down is stored back into the one above it, ending with the variable.
+    /// The variable is read only now, so anything evaluated since it was resolved is kept
+    fn write_target(
+        &mut self,
+        path: TargetPath,
+        update: impl FnOnce(&ExpressionResult) -> Result<ExpressionResult, Thrown>,
+    ) -> Result<(ExpressionResult, ExpressionResult), Thrown> {
+        let (root_value, previous, new) = replace_at(self.target_root(&path), &path.keys, update)?;
+        if let Expression::Identifier(identifier) = path.root {
+            self.env.set_variable(identifier.clone(), root_value);
+        }
+        Ok((previous, new))
     }
 }
 
+/// Where an assignment writes: a variable, or a container that isn't held in one, followed by the
+/// property names and indices leading down from it
+struct TargetPath<'e> {
+    root: &'e Expression,
+    detached_root: Option<ExpressionResult>,
+    keys: Vec<ExpressionResult>,
+}
+
+// Applies `update` to the value found by following `keys` down from `container`, returning the changed
+// container along with the value before and after the update
+fn replace_at(
+    container: ExpressionResult,
+    keys: &[ExpressionResult],
+    update: impl FnOnce(&ExpressionResult) -> Result<ExpressionResult, Thrown>,
+) -> Result<(ExpressionResult, ExpressionResult, ExpressionResult), Thrown> {
+    let Some((key, rest)) = keys.split_first() else {
+        let new = update(&container)?;
+        return Ok((new.clone(), container, new));
+    };
+    let (child, previous, new) = replace_at(get_index(&container, key), rest, update)?;
+    let mut container = container;
+    set_index(&mut container, key, child)?;
+    Ok((container, previous, new))
+}
+
 impl<'a> NodeVisitor for Evaluator<'a> {
-        fn visit_statement(&mut self, statement: &Statement) -> Option<ExpressionResult> {
-        let repeat_statement = statement.clone();
+        fn visit_statement(&mut self, statement: &Statement) -> Option<Completion> {
         match statement {
             Statement::Let(identifier, expression) => {
                 let result = expression.accept(self);
@@ -142,74 +358,240 @@ impl<'a> NodeVisitor for Evaluator<'a> {
                     Ok(val) => {
                         self.env.define_variable(identifier.clone(), val);
                     }
-                    Err(error) => {
-                        println!("{:#?}", error);
-                    }
+                    Err(error) => return Some(Completion::Throw(error)),
                 }
                 return None;
             }
+            Statement::LetPattern(pattern, expression) => {
+                let result = expression
+                    .accept(self)
+                    .and_then(|value| self.bind_pattern(pattern, value));
+                if let Err(error) = result {
+                    return Some(Completion::Throw(error));
+                }
+                None
+            }
             Statement::ExpressionStatement(expression) => {
                 let result = expression.accept(self);
                 if let Ok(value) = result {
-                    println!("{}", value)
+                    self.env.output.print_result(&value);
+                    self.env.last_value = value;
                 } else if let Err(error) = result {
-                    println!("{:#?}", error)
+                    return Some(Completion::Throw(error));
                 }
                 return None;
             }
             Statement::ReturnStatement(return_expression) => {
                 if let Some(expression) = return_expression {
-                    let result = expression.accept(self);
-                    if let Ok(value) = result {
-                        return Some(value);
+                    return match expression.accept(self) {
+                        Ok(value) => Some(Completion::Return(value)),
+                        Err(error) => Some(Completion::Throw(error)),
+                    };
+                }
+                Some(Completion::Return(ExpressionResult::Undefined))
+            }
+            Statement::Break => Some(Completion::Break),
+            Statement::Continue => Some(Completion::Continue),
+            Statement::Switch(discriminant, cases) => {
+                let value = match discriminant.accept(self) {
+                    Ok(value) => value,
+                    Err(error) => return Some(Completion::Throw(error)),
+                };
+                // cases are tried in order, and only until one matches
+                let mut start = None;
+                for (index, (test, _)) in cases.iter().enumerate() {
+                    let Some(test) = test else { continue };
+                    match test.accept(self) {
+                        Ok(test_value) if strictly_equal(&value, &test_value) => {
+                            start = Some(index);
+                            break;
+                        }
+                        Ok(_) => {}
+                        Err(error) => return Some(Completion::Throw(error)),
+                    }
+                }
+                let start = start.or_else(|| cases.iter().position(|(test, _)| test.is_none()))?;
+                // the cases share one scope, and running falls through from the matching case until a break
+                let mut switch_env = self.env.create_child_env();
+                for (_, statements) in cases {
+                    declare_functions(statements, &mut switch_env);
+                }
+                let mut completion = None;
+                for (_, statements) in &cases[start..] {
+                    completion = run_statements(statements, &mut switch_env);
+                    if completion.is_some() {
+                        break;
+                    }
+                }
+                self.env.merge_child_env(switch_env);
+                match completion {
+                    Some(Completion::Break) => None,
+                    _ => completion,
+                }
+            }
+            Statement::Throw(expression) => match expression.accept(self) {
+                Ok(value) => Some(Completion::Throw(Thrown::Value(Box::new(value)))),
+                Err(error) => Some(Completion::Throw(error)),
+            },
+            Statement::Block(block) => {
+                let mut block_env = self.env.create_child_env();
+                let completion = block.run(&mut block_env);
+                self.env.merge_child_env(block_env);
+                if completion.is_some() {
+                    return completion;
+                }
+                None
+            }
+            Statement::Try(block, catch_clause, finally_block) => {
+                let mut block_env = self.env.create_child_env();
+                let mut completion = block.run(&mut block_env);
+                self.env.merge_child_env(block_env);
+                if let (Some(Completion::Throw(thrown)), Some((identifier, catch_block))) = (&completion, catch_clause) {
+                    let mut catch_env = self.env.create_child_env();
+                    if let Some(identifier) = identifier {
+                        catch_env.define_variable(identifier.clone(), thrown.caught_value());
+                    }
+                    completion = catch_block.run(&mut catch_env);
+                    self.env.merge_child_env(catch_env);
+                }
+                if let Some(finally_block) = finally_block {
+                    let mut finally_env = self.env.create_child_env();
+                    let finally_completion = finally_block.run(&mut finally_env);
+                    self.env.merge_child_env(finally_env);
+                    // a return, break or throw inside finally replaces however the try or catch ended
+                    if finally_completion.is_some() {
+                        return finally_completion;
                     }
                 }
-                Some(ExpressionResult::Undefined)
+                completion
             }
             Statement::ConditionalStatement(condition, block, next_conditional) => {
-                if let Ok(expression_result) = condition.accept(self) {
-                    if expression_result.coerce_to_bool() {
+                match condition.accept(self) {
+                    Ok(expression_result) if expression_result.coerce_to_bool() => {
                         let mut block_env = self.env.create_child_env();
-                        let _block_result = block.execute_block(&mut block_env);
+                        let completion = block.run(&mut block_env);
                         self.env.merge_child_env(block_env);
-                    } else if let Some(next_conditional_statement) = &**next_conditional {
-                        return next_conditional_statement.accept(self);
+                        // a break inside an if has to end the loop around the if, and a return the function around it
+                        if completion.is_some() {
+                            return completion;
+                        }
+                    }
+                    Ok(_) => {
+                        // else and else if are conditionals too, so their blocks get a child environment of their own
+                        if let Some(next_conditional_statement) = &**next_conditional {
+                            return next_conditional_statement.accept(self);
+                        }
                     }
+                    Err(error) => return Some(Completion::Throw(error)),
                 }
                 return None;
             }
             Statement::While(inner_conditional) => {
                 match &**inner_conditional {
                     Statement::ConditionalStatement(condition, block, _next_conditional) => {
-                        if let Ok(expression_result) = condition.accept(self) {
-                            if expression_result.coerce_to_bool() {
+                        match condition.accept(self) {
+                            Ok(expression_result) if expression_result.coerce_to_bool() => {
                                 let mut block_env = self.env.create_child_env();
-                                let _block_result = block.execute_block(&mut block_env);
+                                let completion = block.run(&mut block_env);
                                 self.env.merge_child_env(block_env);
-                                return self.visit_statement(&repeat_statement);
+                                match completion {
+                                    Some(Completion::Break) => return None,
+                                    // a continue only ends this run of the body, so the condition is checked again
+                                    Some(Completion::Continue) | None => {}
+                                    Some(_) => return completion,
+                                }
+                                return self.visit_statement(statement);
                             }
+                            Ok(_) => {}
+                            Err(error) => return Some(Completion::Throw(error)),
                         }
                         return None;
                     }
                     _ => panic!("while statement should only contain conditional statement"),
                 }
             }
-            _ => None, // Function declarations are hoisted, so shouldn't reach here
+            Statement::ForOf(identifier, iterable, block) => {
+                let elements = match iterable.accept(self) {
+                    Ok(ExpressionResult::Array(elements)) => elements,
+                    Ok(value) => {
+                        let error = InterpreterError {
+                            kind: InterpreterErrorKind::TypeError(format!("{} is not iterable", value)),
+                        };
+                        return Some(Completion::Throw(error.to_string().into()));
+                    }
+                    Err(error) => return Some(Completion::Throw(error)),
+                };
+                for element in elements {
+                    let mut block_env = self.env.create_child_env();
+                    block_env.define_variable(identifier.clone(), element);
+                    let completion = block.run(&mut block_env);
+                    self.env.merge_child_env(block_env);
+                    match completion {
+                        Some(Completion::Break) => break,
+                        Some(Completion::Continue) | None => {}
+                        Some(_) => return completion,
+                    }
+                }
+                None
+            }
+            Statement::For(init, condition, update, block) => {
+                // the loop gets its own environment so a variable declared by init only lives as long as the loop
+                let mut loop_env = self.env.create_child_env();
+                let mut loop_evaluator = Evaluator::new(&mut loop_env);
+                let mut outcome = init.as_ref().and_then(|init| init.accept(&mut loop_evaluator));
+                while outcome.is_none() {
+                    if let Some(condition) = condition {
+                        match condition.accept(&mut loop_evaluator) {
+                            Ok(value) if value.coerce_to_bool() => {}
+                            Ok(_) => break,
+                            Err(error) => {
+                                outcome = Some(Completion::Throw(error));
+                                break;
+                            }
+                        }
+                    }
+                    let mut block_env = loop_evaluator.env.create_child_env();
+                    let completion = block.run(&mut block_env);
+                    loop_evaluator.env.merge_child_env(block_env);
+                    match completion {
+                        Some(Completion::Break) => break,
+                        Some(Completion::Continue) | None => {}
+                        Some(_) => {
+                            outcome = completion;
+                            break;
+                        }
+                    }
+                    if let Some(update) = update {
+                        if let Err(error) = update.accept(&mut loop_evaluator) {
+                            outcome = Some(Completion::Throw(error));
+                        }
+                    }
+                }
+                self.env.merge_child_env(loop_env);
+                outcome
+            }
+            // Function declarations are hoisted, so shouldn't reach here
+            Statement::FunctionDeclaration(_, _, _) => None,
         }
     }
 
     fn visit_expression(
         &mut self,
         expression: &Expression,
-    ) -> Result<ExpressionResult, String> {
+    ) -> Result<ExpressionResult, Thrown> {
         match expression {
             Expression::NumberLiteral(n) => Ok(ExpressionResult::Number(*n)),
             Expression::Identifier(identifier) => match self.env.get_variable(identifier) {
                 Some(value) => Ok(value),
+                // declared functions live apart from variables, but can still be used as values
+                None if self.env.has_function(identifier.clone()) => Ok(ExpressionResult::Function(
+                    identifier.clone(),
+                    self.env.get_function(identifier).unwrap(),
+                )),
                 None => Err(InterpreterError {
                     kind: InterpreterErrorKind::ReferenceError(identifier.clone()),
                 }
-                .to_string()),
+                .to_string().into()),
             },
             Expression::Boolean(is_true) => {
                 if *is_true {
@@ -218,10 +600,29 @@ impl<'a> NodeVisitor for Evaluator<'a> {
                     Ok(ExpressionResult::Boolean(false))
                 }
             }
+            Expression::Null => Ok(ExpressionResult::Null),
+            Expression::Undefined => Ok(ExpressionResult::Undefined),
             Expression::String(string) => Ok(ExpressionResult::String(string.clone())),
             Expression::Prefix(operator, expression) => {
                 self.evaluate_prefix_expression(operator, expression)
             }
+            Expression::Postfix(operand, operator) => match &**operand {
+                Expression::Identifier(_) | Expression::Member(_, _) | Expression::Index(_, _) => {
+                    let prefix = match operator {
+                        PostfixOperator::Increment => PrefixOperator::Increment,
+                        PostfixOperator::Decrement => PrefixOperator::Decrement,
+                    };
+                    // the new value is a number, so the old value is returned as a number too
+                    let (previous, _) = self.modify_target_and_return_values(&prefix, operand)?;
+                    Ok(ExpressionResult::Number(previous))
+                }
+                _ => Err(InterpreterError {
+                    kind: InterpreterErrorKind::SyntaxError(Some(
+                        SyntaxErrorKind::InvalidLeftSidePostfix,
+                    )),
+                }
+                .to_string().into()),
+            },
             Expression::Operation(left_hand, operator, right_hand) => {
                 self.evaluate_operation_expression(left_hand, operator, right_hand)
             }
@@ -237,27 +638,141 @@ impl<'a> NodeVisitor for Evaluator<'a> {
                         Err(InterpreterError {
                             kind: InterpreterErrorKind::ReferenceError(identifier.clone()),
                         }
-                        .to_string())
+                        .to_string().into())
                     }
                 }
+                Expression::Member(_, _) | Expression::Index(_, _) => {
+                    let path = self.resolve_target(left_hand)?;
+                    let value = right_hand.accept(self)?;
+                    self.write_target(path, |_| Ok(value.clone()))?;
+                    Ok(value)
+                }
                 _ => Err(InterpreterError {
                     kind: InterpreterErrorKind::SyntaxError(Some(
                         SyntaxErrorKind::LeftSideAssignmentMustBeIdentifier,
                     )),
                 }
-                .to_string()),
+                .to_string().into()),
             },
+            Expression::LogicalAssignment(target, operator, value) => {
+                let path = self.resolve_target(target)?;
+                let current = self.read_target(&path);
+                let should_assign = match operator {
+                    Operator::Or => !current.coerce_to_bool(),
+                    Operator::And => current.coerce_to_bool(),
+                    _ => current.is_nullish(),
+                };
+                if !should_assign {
+                    return Ok(current);
+                }
+                let value = value.accept(self)?;
+                self.write_target(path, |_| Ok(value.clone()))?;
+                Ok(value)
+            }
+            Expression::CompoundAssignment(target, operator, value) => {
+                let path = self.resolve_target(target)?;
+                let current = self.read_target(&path);
+                let value = value.accept(self)?;
+                let result = get_operator_strategy(operator.clone()).apply(current, value, self.env)?;
+                self.write_target(path, |_| Ok(result.clone()))?;
+                Ok(result)
+            }
             Expression::Call(callee, arguments) => match &**callee {
                 Expression::Identifier(identifier) => {
                     if let Some(function) = self.env.get_function(identifier) {
-                        return function.call(arguments.clone(), self.env);
+                        let values = self.evaluate_expression_list(arguments)?;
+                        return function.call(values, self.env);
                     }
-                    return Err(format!("Function {} not defined", identifier));
+                    match self.env.get_variable(identifier) {
+                        Some(ExpressionResult::NativeFunction(native)) => {
+                            let values = self.evaluate_expression_list(arguments)?;
+                            return Ok(native.call(values)?);
+                        }
+                        Some(ExpressionResult::Function(_, function)) => {
+                            let values = self.evaluate_expression_list(arguments)?;
+                            return function.call(values, self.env);
+                        }
+                        _ => {}
+                    }
+                    return Err(format!("Function {} not defined", identifier).into());
                 }
-                _ => {
-                    return Err("Either not implemented or not valid".into());
+                Expression::Member(object, method) => {
+                    let mut value = object.accept(self)?;
+                    let values = self.evaluate_expression_list(arguments)?;
+                    // script functions need the environment to run in, which call_method doesn't have
+                    if let ExpressionResult::Object(properties) = &value {
+                        if let Some(ExpressionResult::Function(_, function)) = properties.get(method) {
+                            return function.call_with_this(value.clone(), values, self.env);
+                        }
+                    }
+                    if let (ExpressionResult::Array(elements), "map" | "filter") = (&value, method.as_str()) {
+                        return self.call_array_callback_method(elements.clone(), method, values);
+                    }
+                    let mutates = is_mutating_method(&value, method);
+                    let result = call_method(&mut value, method, values);
+                    // write the changed value back so methods like push are visible through the variable
+                    if let (true, Expression::Identifier(identifier)) = (mutates, &**object) {
+                        self.env.set_variable(identifier.clone(), value);
+                    }
+                    Ok(result?)
                 }
+                // anything else that evaluates to a function, such as list[0]() or make()()
+                _ => match callee.accept(self)? {
+                    function @ (ExpressionResult::NativeFunction(_) | ExpressionResult::Function(_, _)) => {
+                        let values = self.evaluate_expression_list(arguments)?;
+                        self.call_value(function, values)
+                    }
+                    _ => Err("Either not implemented or not valid".to_string().into()),
+                },
             },
+            Expression::Array(elements) => {
+                Ok(ExpressionResult::Array(self.evaluate_expression_list(elements)?))
+            }
+            Expression::Member(object, property) => {
+                let value = object.accept(self)?;
+                Ok(get_property(&value, property))
+            }
+            Expression::Index(object, index) => {
+                let value = object.accept(self)?;
+                let index_value = index.accept(self)?;
+                Ok(get_index(&value, &index_value))
+            }
+            Expression::Object(properties) => {
+                let mut object = Object::new();
+                for (key, value) in properties {
+                    object.set(key.clone(), value.accept(self)?);
+                }
+                Ok(ExpressionResult::Object(object))
+            }
+            Expression::This => Ok(self.env.this.clone()),
+            Expression::Function(name, parameters, block) => Ok(ExpressionResult::Function(
+                name.clone(),
+                Function::new(parameters.clone(), block.clone()).capturing(self.env),
+            )),
+            Expression::ArrowFunction(parameters, block) => Ok(ExpressionResult::Function(
+                String::new(),
+                Function::arrow(parameters.clone(), block.clone(), self.env.this.clone()).capturing(self.env),
+            )),
+            Expression::Conditional(condition, consequent, alternate) => {
+                if condition.accept(self)?.coerce_to_bool() {
+                    consequent.accept(self)
+                } else {
+                    alternate.accept(self)
+                }
+            }
+            Expression::Sequence(expressions) => {
+                let mut result = ExpressionResult::Undefined;
+                for expression in expressions {
+                    result = expression.accept(self)?;
+                }
+                Ok(result)
+            }
+            Expression::Spread(_) => Err(InterpreterError {
+                kind: InterpreterErrorKind::SyntaxError(Some(SyntaxErrorKind::UnexpectedToken(
+                    Token::Dot,
+                ))),
+            }
+            .to_string().into()),
         }
     }
 }
@@ -1,5 +1,7 @@
 use std::hash::{Hash, Hasher};
 
+use crate::interpreter::errors::{LexError, SyntaxErrorKind};
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum Token {
     Let,
@@ -32,6 +34,32 @@ pub enum Token {
     Else,
     While,
     Percent,
+    LeftSquareBracket,
+    RightSquareBracket,
+    For,
+    Dot,
+    Colon,
+    Null,
+    Break,
+    QuestionMark,
+    Try,
+    Catch,
+    TypeOf,
+    Continue,
+    Delete,
+    In,
+    Throw,
+    Finally,
+    Switch,
+    Case,
+    Default,
+    // the text of a string that was still open when the source ended
+    UnterminatedString(String),
+    // a character that can't start any token, like @ or #
+    Unknown(String),
+    This,
+    // a word starting with a digit that isn't a number, like 2a, which can't be an identifier either
+    InvalidIdentifier(String),
 }
 
 impl Eq for Token {}
@@ -42,11 +70,46 @@ impl Hash for Token {
     }
 }
 
+/// Splits source into tokens.  Source that can't be read becomes an error token for the parser to report,
+/// see `tokenize_checked` to get the error and where it is instead
 pub fn tokenize(input: &str) -> Vec<Token> {
+    lex(input).0
+}
+
+/// Splits source into tokens like `tokenize`, but stops at the first source that can't be read,
+/// so lexing errors can be told apart from parsing errors
+pub fn tokenize_checked(input: &str) -> Result<Vec<Token>, LexError> {
+    match lex(input) {
+        (tokens, None) => Ok(tokens),
+        (_, Some(error)) => Err(error),
+    }
+}
+
+/// The error for a token the lexer produced from source it couldn't read
+pub(crate) fn lexer_error(token: &Token) -> Option<SyntaxErrorKind> {
+    match token {
+        Token::UnterminatedString(_) => Some(SyntaxErrorKind::UnterminatedStringLiteral),
+        Token::Unknown(character) => Some(SyntaxErrorKind::UnexpectedCharacter(character.clone())),
+        Token::InvalidIdentifier(text) => Some(SyntaxErrorKind::InvalidOrUnexpectedToken(text.clone())),
+        _ => None,
+    }
+}
+
+// Tokenizes the whole input, along with the first error token and where the source it came from starts
+fn lex(input: &str) -> (Vec<Token>, Option<LexError>) {
     let mut tokens: Vec<Token> = Vec::new();
     let mut current_string: String = String::new();
     let mut is_reading_string: bool = false;
-    input.chars().for_each(|character| {
+    let mut first_error: Option<LexError> = None;
+    // lines and columns of the current character, the word being read and the last opening quote
+    let mut position = (1, 1);
+    let mut word_start = position;
+    let mut string_start = position;
+    for character in input.chars() {
+        let tokens_before = tokens.len();
+        if !is_reading_string && current_string.is_empty() {
+            word_start = position;
+        }
         if is_reading_string {
             match character {
                 '"' => {
@@ -127,6 +190,7 @@ pub fn tokenize(input: &str) -> Vec<Token> {
                     evaluate_current_string(&mut tokens, &mut current_string);
                     tokens.push(Token::DoubleQuote);
                     is_reading_string = true;
+                    string_start = position;
                 }
                 '\n' => {
                     evaluate_current_string(&mut tokens, &mut current_string);
@@ -141,38 +205,113 @@ pub fn tokenize(input: &str) -> Vec<Token> {
                     evaluate_current_string(&mut tokens, &mut current_string);
                     tokens.push(Token::Percent);
                 }
-                _ => {
+                '.' => {
+                    // a dot directly after digits is a decimal point rather than its own token
+                    if is_string_digits(&current_string) {
+                        current_string.push(character);
+                    } else {
+                        evaluate_current_string(&mut tokens, &mut current_string);
+                        tokens.push(Token::Dot);
+                    }
+                }
+                ':' => {
+                    evaluate_current_string(&mut tokens, &mut current_string);
+                    tokens.push(Token::Colon);
+                }
+                '?' => {
+                    evaluate_current_string(&mut tokens, &mut current_string);
+                    tokens.push(Token::QuestionMark);
+                }
+                '[' => {
+                    evaluate_current_string(&mut tokens, &mut current_string);
+                    tokens.push(Token::LeftSquareBracket);
+                }
+                ']' => {
+                    evaluate_current_string(&mut tokens, &mut current_string);
+                    tokens.push(Token::RightSquareBracket);
+                }
+                // letters, digits, _ and $ make up identifiers, keywords and numbers
+                _ if character.is_alphanumeric() || character == '_' || character == '$' => {
                     current_string.push(character);
                 }
+                _ if character.is_whitespace() => {
+                    evaluate_current_string(&mut tokens, &mut current_string);
+                    current_string.clear();
+                }
+                _ => {
+                    evaluate_current_string(&mut tokens, &mut current_string);
+                    tokens.push(Token::Unknown(character.to_string()));
+                }
             }
         }
-    });
-    if string_has_non_whitespace(&current_string) {
+        if first_error.is_none() {
+            first_error = find_lex_error(&tokens[tokens_before..], position, word_start, string_start);
+        }
+        position = match character {
+            '\n' => (position.0 + 1, 1),
+            _ => (position.0, position.1 + 1),
+        };
+    }
+    if is_reading_string {
+        // the opening quote is the last token pushed, as the string's characters are only pushed once it closes
+        tokens.pop();
+    }
+    let tokens_before = tokens.len();
+    if is_reading_string {
+        tokens.push(Token::UnterminatedString(current_string));
+    } else if string_has_non_whitespace(&current_string) {
         evaluate_current_string(&mut tokens, &mut current_string);
     }
+    if first_error.is_none() {
+        first_error = find_lex_error(&tokens[tokens_before..], position, word_start, string_start);
+    }
     tokens.push(Token::EOF);
-    tokens
+    (tokens, first_error)
+}
+
+// Finds an error among newly pushed tokens, placed where the source it was read from starts
+fn find_lex_error(
+    new_tokens: &[Token],
+    position: (usize, usize),
+    word_start: (usize, usize),
+    string_start: (usize, usize),
+) -> Option<LexError> {
+    let token = new_tokens.iter().find(|token| lexer_error(token).is_some())?;
+    let (line, column) = match token {
+        Token::InvalidIdentifier(_) => word_start,
+        Token::UnterminatedString(_) => string_start,
+        _ => position,
+    };
+    Some(LexError {
+        kind: lexer_error(token)?,
+        line,
+        column,
+    })
+}
+
+/// Counts the parentheses, curly braces and square brackets that were opened but not yet closed,
+/// so the REPL knows to keep reading lines.  Brackets inside strings are already part of a string token.
+pub fn count_unclosed_brackets(tokens: &[Token]) -> usize {
+    let mut depth: isize = 0;
+    for token in tokens {
+        match token {
+            Token::LeftParen | Token::LeftCurlyBrace | Token::LeftSquareBracket => depth += 1,
+            Token::RightParen | Token::RightCurlyBrace | Token::RightSquareBracket => depth -= 1,
+            _ => {}
+        }
+    }
+    // extra closing brackets can't be fixed by reading more, so leave those for the parser to report
+    depth.max(0) as usize
 }
 
 fn evaluate_current_string(tokens: &mut Vec<Token>, current_string: &mut String) {
     if string_has_non_whitespace(current_string) {
-        if *current_string == "let" {
-            tokens.push(Token::Let);
-        } else if current_string.trim() == "function" {
-            tokens.push(Token::Function);
-        } else if current_string.trim() == "return" {
-            tokens.push(Token::Return);
-        } else if current_string.trim() == "if" {
-            tokens.push(Token::If)  
-        } else if current_string.trim() == "else" {
-            tokens.push(Token::Else)  
-        } else if current_string.trim() == "while" {
-            tokens.push(Token::While)
-        } else if current_string.trim() == "true" || current_string.trim() == "false" {
-            let bool_value = current_string.trim() == "true";
-            tokens.push(Token::Boolean(bool_value));
+        if let Some(keyword) = keyword_token(current_string.trim()) {
+            tokens.push(keyword);
         } else if is_string_a_number(current_string) {
             tokens.push(Token::Number(convert_string_to_f64(current_string)));
+        } else if current_string.trim().starts_with(|character: char| character.is_ascii_digit()) {
+            tokens.push(Token::InvalidIdentifier(current_string.trim().to_string()));
         } else {
             tokens.push(Token::Ident(current_string.clone()));
         }
@@ -180,9 +319,52 @@ fn evaluate_current_string(tokens: &mut Vec<Token>, current_string: &mut String)
     current_string.clear();
 }
 
+// Reserved words lex to their own tokens, so they can never be used as identifiers.
+// Each one is listed once, so adding a keyword is one edit here
+const KEYWORDS: [(&str, Token); 23] = [
+    ("let", Token::Let),
+    ("function", Token::Function),
+    ("return", Token::Return),
+    ("if", Token::If),
+    ("else", Token::Else),
+    ("while", Token::While),
+    ("for", Token::For),
+    ("break", Token::Break),
+    ("continue", Token::Continue),
+    ("try", Token::Try),
+    ("catch", Token::Catch),
+    ("finally", Token::Finally),
+    ("delete", Token::Delete),
+    ("this", Token::This),
+    ("switch", Token::Switch),
+    ("case", Token::Case),
+    ("default", Token::Default),
+    ("throw", Token::Throw),
+    ("in", Token::In),
+    ("typeof", Token::TypeOf),
+    ("null", Token::Null),
+    ("true", Token::Boolean(true)),
+    ("false", Token::Boolean(false)),
+];
+
+fn keyword_token(word: &str) -> Option<Token> {
+    KEYWORDS.iter().find(|(keyword, _)| *keyword == word).map(|(_, token)| token.clone())
+}
+
+/// The word a keyword token was read from, since keywords can still name properties, as in `o.default`
+pub(crate) fn keyword_name(token: &Token) -> Option<&'static str> {
+    KEYWORDS.iter().find(|(_, keyword)| keyword == token).map(|(word, _)| *word)
+}
+
+// Numbers start with a digit, which also keeps words Rust would parse as numbers, like nan and inf, as identifiers
 fn is_string_a_number(current_string: &String) -> bool {
-    let result = current_string.trim().parse::<f64>();
-    result.is_ok()
+    let trimmed = current_string.trim();
+    trimmed.starts_with(|character: char| character.is_ascii_digit()) && trimmed.parse::<f64>().is_ok()
+}
+
+fn is_string_digits(current_string: &str) -> bool {
+    let trimmed = current_string.trim();
+    !trimmed.is_empty() && trimmed.chars().all(|character| character.is_ascii_digit())
 }
 
 fn convert_string_to_f64(current_string: &String) -> f64 {
@@ -492,6 +674,38 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn it_parses_square_brackets() {
+        let result: Vec<Token> = tokenize("[1, 2]");
+        let expected = [
+            Token::LeftSquareBracket,
+            Token::Number(1.0),
+            Token::Comma,
+            Token::Number(2.0),
+            Token::RightSquareBracket,
+            Token::EOF
+        ];
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn it_parses_dots_outside_of_numbers() {
+        let result: Vec<Token> = tokenize("f(...args, 1.5)");
+        let expected = [
+            Token::Ident("f".into()),
+            Token::LeftParen,
+            Token::Dot,
+            Token::Dot,
+            Token::Dot,
+            Token::Ident("args".into()),
+            Token::Comma,
+            Token::Number(1.5),
+            Token::RightParen,
+            Token::EOF
+        ];
+        assert_eq!(result, expected);
+    }
+
     #[test]
     fn it_parses_if() {
         let result: Vec<Token> = tokenize("if");
@@ -501,4 +715,137 @@ mod tests {
         ];
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn it_should_count_unclosed_brackets_outside_strings() {
+        assert_eq!(count_unclosed_brackets(&tokenize("function f() {")), 1);
+        assert_eq!(count_unclosed_brackets(&tokenize("f([1, \"{\"")), 2);
+        assert_eq!(count_unclosed_brackets(&tokenize("if (x) { y; }")), 0);
+    }
+
+    #[test]
+    fn it_marks_a_string_left_open_at_the_end_of_the_source() {
+        let result: Vec<Token> = tokenize("let x = \"abc");
+        let expected = [
+            Token::Let,
+            Token::Ident("x".into()),
+            Token::Equals,
+            Token::UnterminatedString("abc".into()),
+            Token::EOF
+        ];
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn it_reports_characters_that_start_no_token() {
+        let result: Vec<Token> = tokenize("$a_1 @b~");
+        let expected = [
+            Token::Ident("$a_1".into()),
+            Token::Unknown("@".into()),
+            Token::Ident("b".into()),
+            Token::Unknown("~".into()),
+            Token::EOF
+        ];
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn it_separates_tokens_on_tabs() {
+        let result: Vec<Token> = tokenize("let\tx");
+        assert_eq!(result, [Token::Let, Token::Ident("x".into()), Token::EOF]);
+    }
+
+    #[test]
+    fn it_lexes_identifiers_starting_with_a_letter_underscore_or_dollar() {
+        let result: Vec<Token> = tokenize("let $x = 1; let a2 = 2; _b; nan;");
+        let expected = [
+            Token::Let,
+            Token::Ident("$x".into()),
+            Token::Equals,
+            Token::Number(1.0),
+            Token::Semicolon,
+            Token::Let,
+            Token::Ident("a2".into()),
+            Token::Equals,
+            Token::Number(2.0),
+            Token::Semicolon,
+            Token::Ident("_b".into()),
+            Token::Semicolon,
+            Token::Ident("nan".into()),
+            Token::Semicolon,
+            Token::EOF
+        ];
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn it_rejects_words_starting_with_a_digit() {
+        let result: Vec<Token> = tokenize("2a 2.5");
+        assert_eq!(result, [Token::InvalidIdentifier("2a".into()), Token::Number(2.5), Token::EOF]);
+    }
+
+    #[test]
+    fn it_lexes_every_keyword_to_its_token() {
+        let keywords = [
+            ("let", Token::Let),
+            ("function", Token::Function),
+            ("return", Token::Return),
+            ("if", Token::If),
+            ("else", Token::Else),
+            ("while", Token::While),
+            ("for", Token::For),
+            ("break", Token::Break),
+            ("continue", Token::Continue),
+            ("try", Token::Try),
+            ("catch", Token::Catch),
+            ("finally", Token::Finally),
+            ("delete", Token::Delete),
+            ("this", Token::This),
+            ("switch", Token::Switch),
+            ("case", Token::Case),
+            ("default", Token::Default),
+            ("throw", Token::Throw),
+            ("in", Token::In),
+            ("typeof", Token::TypeOf),
+            ("null", Token::Null),
+            ("true", Token::Boolean(true)),
+            ("false", Token::Boolean(false)),
+        ];
+        for (word, token) in keywords {
+            assert_eq!(tokenize(word), [token, Token::EOF], "{}", word);
+        }
+    }
+
+    #[test]
+    fn tokenize_checked_should_return_tokens_for_valid_source() {
+        assert_eq!(tokenize_checked(BASIC_TEST_STRING), Ok(tokenize(BASIC_TEST_STRING)));
+    }
+
+    #[test]
+    fn tokenize_checked_should_report_where_unreadable_source_starts() {
+        assert_eq!(
+            tokenize_checked("let x = 1;\nlet y = @;"),
+            Err(LexError {
+                kind: SyntaxErrorKind::UnexpectedCharacter("@".into()),
+                line: 2,
+                column: 9,
+            })
+        );
+        assert_eq!(
+            tokenize_checked("let 2a = 1; #"),
+            Err(LexError {
+                kind: SyntaxErrorKind::InvalidOrUnexpectedToken("2a".into()),
+                line: 1,
+                column: 5,
+            })
+        );
+        assert_eq!(
+            tokenize_checked("let s =\n  \"open"),
+            Err(LexError {
+                kind: SyntaxErrorKind::UnterminatedStringLiteral,
+                line: 2,
+                column: 3,
+            })
+        );
+    }
 }
@@ -0,0 +1,153 @@
+use std::{cell::Cell, thread};
+
+use crate::{
+    ast::{ExpressionResult, Statement},
+    environment::Environment,
+    function::INTERPRETER_STACK_SIZE,
+    interpreter::{
+        errors::{ParserError, ParserErrorKind, RunError, SyntaxErrorKind},
+        process_statements,
+    },
+    lexer::tokenize,
+    parser::{separate_out_statements_and_parser_errors, Parser},
+};
+
+pub mod ast;
+pub mod environment;
+pub mod function;
+pub mod interpreter;
+pub mod lexer;
+pub mod object;
+pub mod parser;
+pub mod printer;
+mod integration_tests;
+
+/// How `run` treats a `return` outside of any function
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mode {
+    /// A plain script, where a top level `return` is a syntax error
+    Script,
+    /// A module, where a top level `return value;` ends the module and `value` becomes its result
+    Module,
+}
+
+/// Runs a piece of source code against an environment as a script.
+///
+/// Nothing is evaluated if the source fails to parse, in which case every parser error is returned.
+/// A runtime error nothing caught stops the source where it happened and is returned as well.
+/// Otherwise the value of the last expression statement is returned, and the environment keeps any
+/// variables and functions the source declared, so it can be passed to `run` again, like the REPL does.
+pub fn run(source: &str, env: &mut Environment) -> Result<ExpressionResult, RunError> {
+    run_with_mode(source, env, Mode::Script)
+}
+
+/// Runs a piece of source code like `run`, but in the given mode
+pub fn run_with_mode(
+    source: &str,
+    env: &mut Environment,
+    mode: Mode,
+) -> Result<ExpressionResult, RunError> {
+    if ON_INTERPRETER_STACK.with(Cell::get) {
+        run_on_current_thread(source, env, mode)
+    } else {
+        // the interpreter recurses with the script, so it needs room for deep call stacks,
+        // which lets callers on any thread reach the default call depth
+        with_interpreter_stack(|| run_on_current_thread(source, env, mode))
+    }
+}
+
+thread_local! {
+    // set on threads started by with_interpreter_stack, where `run` can evaluate without starting another
+    static ON_INTERPRETER_STACK: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Calls `f` on a new thread with a stack of `INTERPRETER_STACK_SIZE`, and returns what it returns.
+///
+/// `run` starts a thread like this for every call unless it is already on one, so a caller that runs
+/// a lot of source, like the REPL, can start one here once and call `run` from inside it.
+pub fn with_interpreter_stack<T: Send>(f: impl FnOnce() -> T + Send) -> T {
+    thread::scope(|scope| {
+        thread::Builder::new()
+            .stack_size(INTERPRETER_STACK_SIZE)
+            .spawn_scoped(scope, || {
+                ON_INTERPRETER_STACK.with(|on_interpreter_stack| on_interpreter_stack.set(true));
+                f()
+            })
+            .expect("could not start the interpreter thread")
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    })
+}
+
+fn run_on_current_thread(
+    source: &str,
+    env: &mut Environment,
+    mode: Mode,
+) -> Result<ExpressionResult, RunError> {
+    let tokens = tokenize(source);
+    let mut parser = Parser::new(tokens);
+    let statement_results = parser.parse();
+
+    let (statements, mut parser_errors) = separate_out_statements_and_parser_errors(statement_results);
+    if mode == Mode::Script {
+        for _ in 0..count_top_level_returns(&statements) {
+            parser_errors.push(ParserError {
+                kind: ParserErrorKind::SyntaxError(Some(SyntaxErrorKind::IllegalReturnStatement)),
+            });
+        }
+    }
+    if !parser_errors.is_empty() {
+        return Err(RunError::Parser(parser_errors));
+    }
+    // nothing is left to catch a runtime error, so the rest of the source is skipped and the caller decides how to show it
+    process_statements(statements, env).map_err(RunError::Runtime)
+}
+
+/// Parses source code and serializes the result of every statement to pretty printed JSON,
+/// so parser output can be saved as a golden file and diffed across versions.
+/// Statements that fail to parse are serialized as `{ "Err": message }`.
+#[cfg(feature = "serde")]
+pub fn parse_to_json(source: &str) -> String {
+    let mut parser = Parser::new(tokenize(source));
+    let statement_results: Vec<Result<Statement, String>> = parser
+        .parse()
+        .into_iter()
+        .map(|result| result.map_err(|error| error.to_string()))
+        .collect();
+    serde_json::to_string_pretty(&statement_results).expect("the AST always serializes")
+}
+
+// Counts the return statements that are not inside a function, including those nested in ifs and loops
+fn count_top_level_returns(statements: &[Statement]) -> usize {
+    statements
+        .iter()
+        .map(|statement| match statement {
+            Statement::ReturnStatement(_) => 1,
+            Statement::ConditionalStatement(_, block, else_branch) => {
+                count_top_level_returns(block.statements())
+                    + else_branch
+                        .as_ref()
+                        .as_ref()
+                        .map_or(0, |else_branch| count_top_level_returns(std::slice::from_ref(else_branch)))
+            }
+            Statement::While(conditional) => count_top_level_returns(std::slice::from_ref(conditional)),
+            Statement::ForOf(_, _, block) | Statement::For(_, _, _, block) | Statement::Block(block) => {
+                count_top_level_returns(block.statements())
+            }
+            Statement::Switch(_, cases) => cases
+                .iter()
+                .map(|(_, statements)| count_top_level_returns(statements))
+                .sum(),
+            Statement::Try(block, catch_clause, finally_block) => {
+                count_top_level_returns(block.statements())
+                    + catch_clause
+                        .as_ref()
+                        .map_or(0, |(_, catch_block)| count_top_level_returns(catch_block.statements()))
+                    + finally_block
+                        .as_ref()
+                        .map_or(0, |finally_block| count_top_level_returns(finally_block.statements()))
+            }
+            _ => 0,
+        })
+        .sum()
+}
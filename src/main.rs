@@ -1,18 +1,66 @@
-use environment::Environment;
-use lexer::tokenize;
-use parser::Parser;
-use crate::{interpreter::{process_statements}, parser::separate_out_statements_and_parser_errors};
-
-mod lexer;
-mod ast;
-mod parser;
-mod environment;
-mod interpreter;
-mod integration_tests;
-mod function;
+use std::{env, fs, process};
+
+use toy_js_engine::{
+    environment::Environment,
+    interpreter::{errors::RunError, output::Output},
+    lexer::{count_unclosed_brackets, tokenize},
+    run, with_interpreter_stack,
+};
 
 fn main() {
+    // everything runs on one thread with a large stack, rather than `run` starting a new one for each input
+    with_interpreter_stack(|| match env::args().nth(1) {
+        // --types annotates every printed result with its type, like `5 : number`
+        Some(flag) if flag == "--types" => repl(true),
+        Some(path) => run_file(&path),
+        None => repl(false),
+    })
+}
+
+// Runs a whole script at once, so statements can span as many lines as they need
+fn run_file(path: &str) {
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(error) => {
+            eprintln!("Could not read {}: {}", path, error);
+            process::exit(1);
+        }
+    };
+
     let mut env = Environment::new();
+    if let Err(error) = run(&source, &mut env) {
+        print_run_error(error);
+        process::exit(1);
+    }
+}
+
+// Errors are printed plainly, like results, so `Uncaught 5` rather than a quoted string
+fn print_run_error(error: RunError) {
+    match error {
+        RunError::Parser(parser_errors) => {
+            for error in parser_errors {
+                println!("{}", error)
+            }
+        }
+        RunError::Runtime(message) => println!("{}", message),
+    }
+}
+
+// Runs a file against the prompt's environment, so what it defines can be used at the prompt afterwards
+fn load_file(path: &str, env: &mut Environment) {
+    match fs::read_to_string(path) {
+        Ok(source) => {
+            if let Err(error) = run(&source, env) {
+                print_run_error(error);
+            }
+        }
+        Err(error) => println!("Could not read {}: {}", path, error),
+    }
+}
+
+fn repl(annotate_types: bool) {
+    let mut env = Environment::new();
+    env.output = Output::stdout().with_type_annotations(annotate_types);
     loop {
         let mut input = String::new();
         print!("> ");
@@ -22,20 +70,30 @@ fn main() {
             break;
         }
 
-        let tokens = tokenize(&input);
-        let mut parser = Parser::new(tokens);
-        let statement_results = parser.parse();
-
-        let (statements, parser_errors) = separate_out_statements_and_parser_errors(statement_results);
+        // checked before tokenizing, since a path isn't source and may hold unbalanced brackets
+        if let Some(path) = input.trim().strip_prefix(".load ") {
+            load_file(path.trim(), &mut env);
+            continue;
+        }
 
-        if parser_errors.len() > 0 {
-            for error in parser_errors {
-                println!("{}", error)
+        // keep reading lines until every bracket is closed, so a function can be typed over several lines
+        while count_unclosed_brackets(&tokenize(&input)) > 0 {
+            print!("... ");
+            if std::io::stdin().read_line(&mut input).unwrap() == 0 {
+                break;
             }
-        } else {
-            process_statements(statements, &mut env);
         }
-    }
-}
 
+        // .env can't be a statement, so it never hides a variable named env
+        if input.trim() == ".env" {
+            println!("{}", env.dump());
+            continue;
+        }
 
+        match run(&input, &mut env) {
+            // keep the last result around as _, so it can be used in the next input
+            Ok(value) => env.define_variable("_".to_string(), value),
+            Err(error) => print_run_error(error),
+        }
+    }
+}
@@ -0,0 +1,75 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::ast::ExpressionResult;
+
+// An Object is a list of properties kept in insertion order, which is the order JS reports keys in
+#[derive(Clone, Debug, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Object {
+    properties: Vec<(String, ExpressionResult)>,
+}
+
+impl Object {
+    pub fn new() -> Self {
+        Object { properties: vec![] }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&ExpressionResult> {
+        self.properties
+            .iter()
+            .find(|(property, _)| property == key)
+            .map(|(_, value)| value)
+    }
+
+    pub fn keys(&self) -> Vec<String> {
+        self.properties.iter().map(|(key, _)| key.clone()).collect()
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &(String, ExpressionResult)> {
+        self.properties.iter()
+    }
+
+    // Returns whether the key was there to remove
+    pub fn remove(&mut self, key: &str) -> bool {
+        let length = self.properties.len();
+        self.properties.retain(|(property, _)| property != key);
+        self.properties.len() != length
+    }
+
+    // Setting an existing key replaces its value but keeps its original position
+    pub fn set(&mut self, key: String, value: ExpressionResult) {
+        match self.properties.iter_mut().find(|(property, _)| *property == key) {
+            Some((_, existing)) => *existing = value,
+            None => self.properties.push((key, value)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_replace_existing_key() {
+        let mut object = Object::new();
+        object.set("a".into(), ExpressionResult::Number(1.0));
+        object.set("a".into(), ExpressionResult::Number(2.0));
+        assert_eq!(object.get("a"), Some(&ExpressionResult::Number(2.0)));
+    }
+
+    #[test]
+    fn it_should_remove_a_key() {
+        let mut object = Object::new();
+        object.set("a".into(), ExpressionResult::Number(1.0));
+        assert!(object.remove("a"));
+        assert!(!object.remove("a"));
+        assert_eq!(object.get("a"), None);
+    }
+
+    #[test]
+    fn it_should_return_none_for_missing_key() {
+        let object = Object::new();
+        assert_eq!(object.get("a"), None);
+    }
+}
@@ -15,11 +15,30 @@ impl StatementParselet for LetParselet {
         parser: &mut Parser
     ) -> Result<Statement, ParserError> {
         parser.advance();
+        if matches!(parser.peek(), Token::LeftSquareBracket | Token::LeftCurlyBrace) {
+            let pattern = parser.parse_pattern()?;
+            if parser.expect(&Token::Equals) {
+                let expr = parser.parse_assignment();
+                parser.expect(&Token::Semicolon);
+                return Ok(Statement::LetPattern(pattern, expr));
+            }
+            return Err(parser.unexpected_token());
+        }
+        if !matches!(parser.peek(), Token::Ident(_)) {
+            return Err(parser.unexpected_token());
+        }
         if let Token::Ident(name) = parser.advance() {
             if parser.expect(&Token::Equals) {
-                let expr = parser.parse_expression();
+                let expr = parser.parse_assignment();
                 parser.expect(&Token::Semicolon);
                 Ok(Statement::Let(name.clone(), expr))
+            } else if matches!(
+                parser.peek_keep_white_space(),
+                Token::Semicolon | Token::NewLine | Token::EOF | Token::RightCurlyBrace
+            ) {
+                // let x; declares x without a value
+                parser.expect(&Token::Semicolon);
+                Ok(Statement::Let(name.clone(), Expression::Undefined))
             } else {
                 Err(parser.unexpected_token())
             }
@@ -40,10 +59,9 @@ impl StatementParselet for FunctionParselet {
         if let Token::Ident(name) = parser.advance() {
             if parser.expect(&Token::LeftParen) {
                 // building arguments
-                let arguments = parser.parse_arguments();
-                if let Ok(block) = parser.parse_block() {
-                    return Ok(Statement::FunctionDeclaration(name, arguments, block));
-                }
+                let arguments = parser.parse_parameters()?;
+                let block = parser.parse_block()?;
+                return Ok(Statement::FunctionDeclaration(name, arguments, block));
             }
         }
         Err(parser.unexpected_token())
@@ -54,11 +72,18 @@ struct ReturnParselet;
 impl StatementParselet for ReturnParselet {
     fn parse(&self, parser: &mut Parser) -> Result<Statement, ParserError> {
         parser.advance(); // get rid of that return token
-        if !matches!(parser.peek(), Token::Semicolon | Token::NewLine) {
+        // a line break straight after return ends the statement, so `return\n5` returns nothing
+        if !matches!(
+            parser.peek_keep_white_space(),
+            Token::Semicolon | Token::NewLine | Token::RightCurlyBrace | Token::EOF
+        ) {
             let expression = parser.parse_expression();
             parser.expect(&Token::Semicolon);
             return Ok(Statement::ReturnStatement(Some(expression)));
         }
+        if parser.peek_keep_white_space() == &Token::Semicolon {
+            parser.advance();
+        }
         Ok(Statement::ReturnStatement(None))
     }
 }
@@ -110,6 +135,155 @@ impl StatementParselet for WhileParselet {
     }
 }
 
+struct ForParselet;
+impl ForParselet {
+    // Parses the rest of for (init; condition; update) once the init has been read
+    fn parse_for_sections(
+        &self,
+        parser: &mut Parser,
+        init: Option<Box<Statement>>,
+    ) -> Result<Statement, ParserError> {
+        let condition = if parser.peek() == &Token::Semicolon {
+            None
+        } else {
+            Some(parser.parse_expression())
+        };
+        if !parser.expect(&Token::Semicolon) {
+            return Err(parser.unexpected_token());
+        }
+        let update = if parser.peek() == &Token::RightParen {
+            None
+        } else {
+            Some(parser.parse_expression())
+        };
+        if !parser.expect(&Token::RightParen) {
+            return Err(parser.unexpected_token());
+        }
+        let block = if parser.peek() == &Token::LeftCurlyBrace {
+            parser.parse_block()?
+        } else {
+            parser.parse_statement()?.into_block()
+        };
+        Ok(Statement::For(init, condition, update, block))
+    }
+}
+
+impl StatementParselet for ForParselet {
+    fn parse(&self, parser: &mut Parser) -> Result<Statement, ParserError> {
+        parser.advance(); // clear the for token
+
+        if !parser.expect(&Token::LeftParen) {
+            return Err(parser.unexpected_token());
+        }
+        if parser.expect(&Token::Semicolon) {
+            return self.parse_for_sections(parser, None);
+        }
+        if !parser.expect(&Token::Let) {
+            let init = parser.parse_expression();
+            if !parser.expect(&Token::Semicolon) {
+                return Err(parser.unexpected_token());
+            }
+            return self.parse_for_sections(parser, Some(Box::new(Statement::ExpressionStatement(init))));
+        }
+        let identifier = match parser.advance() {
+            Token::Ident(name) => name,
+            _ => return Err(parser.unexpected_token()),
+        };
+        if parser.expect(&Token::Equals) {
+            let value = parser.parse_assignment();
+            if !parser.expect(&Token::Semicolon) {
+                return Err(parser.unexpected_token());
+            }
+            return self.parse_for_sections(parser, Some(Box::new(Statement::Let(identifier, value))));
+        }
+        // `of` is only a keyword in this position, so it is lexed as an identifier
+        if parser.advance() != Token::Ident("of".into()) {
+            return Err(parser.unexpected_token());
+        }
+        let iterable = parser.parse_expression();
+        if !parser.expect(&Token::RightParen) {
+            return Err(parser.unexpected_token());
+        }
+        let block = parser.parse_block()?;
+
+        Ok(Statement::ForOf(identifier, iterable, block))
+    }
+}
+
+struct BreakParselet;
+impl StatementParselet for BreakParselet {
+    fn parse(&self, parser: &mut Parser) -> Result<Statement, ParserError> {
+        parser.advance(); // clear the break token
+        parser.expect(&Token::Semicolon);
+        Ok(Statement::Break)
+    }
+}
+
+struct ContinueParselet;
+impl StatementParselet for ContinueParselet {
+    fn parse(&self, parser: &mut Parser) -> Result<Statement, ParserError> {
+        parser.advance(); // clear the continue token
+        parser.expect(&Token::Semicolon);
+        Ok(Statement::Continue)
+    }
+}
+
+struct SwitchParselet;
+impl StatementParselet for SwitchParselet {
+    fn parse(&self, parser: &mut Parser) -> Result<Statement, ParserError> {
+        parser.advance(); // clear the switch token
+        let discriminant = parser.parse_paren_wrapped_expression()?;
+        if !parser.expect(&Token::LeftCurlyBrace) {
+            return Err(parser.unexpected_token());
+        }
+        let mut cases = vec![];
+        while !parser.expect(&Token::RightCurlyBrace) {
+            let test = match parser.peek() {
+                Token::Case => {
+                    parser.advance();
+                    Some(parser.parse_expression())
+                }
+                Token::Default => {
+                    parser.advance();
+                    None
+                }
+                _ => return Err(parser.unexpected_token()),
+            };
+            if !parser.expect(&Token::Colon) {
+                return Err(parser.unexpected_token());
+            }
+            // a case runs every statement up to the next case, so its statements aren't wrapped in a block
+            let mut statements = vec![];
+            while !matches!(parser.peek(), Token::Case | Token::Default | Token::RightCurlyBrace | Token::EOF) {
+                if matches!(parser.peek(), Token::Semicolon) {
+                    parser.advance();
+                    continue;
+                }
+                statements.push(parser.parse_statement()?);
+            }
+            cases.push((test, statements));
+        }
+        Ok(Statement::Switch(discriminant, cases))
+    }
+}
+
+struct ThrowParselet;
+impl StatementParselet for ThrowParselet {
+    fn parse(&self, parser: &mut Parser) -> Result<Statement, ParserError> {
+        parser.advance(); // clear the throw token
+        let expression = parser.parse_expression();
+        parser.expect(&Token::Semicolon);
+        Ok(Statement::Throw(expression))
+    }
+}
+
+struct BlockParselet;
+impl StatementParselet for BlockParselet {
+    fn parse(&self, parser: &mut Parser) -> Result<Statement, ParserError> {
+        Ok(Statement::Block(parser.parse_block()?))
+    }
+}
+
 struct StatementExpressionParselet;
 impl StatementParselet for StatementExpressionParselet {
     fn parse(
@@ -131,6 +305,38 @@ impl StatementParselet for StatementExpressionParselet {
     }
 }
 
+struct TryParselet;
+impl StatementParselet for TryParselet {
+    fn parse(&self, parser: &mut Parser) -> Result<Statement, ParserError> {
+        parser.advance();
+        let block = parser.parse_block()?;
+        let mut catch_clause = None;
+        if parser.expect(&Token::Catch) {
+            // the binding can be left out, as in `catch { ... }`
+            let mut identifier = None;
+            if parser.expect(&Token::LeftParen) {
+                match parser.advance() {
+                    Token::Ident(name) => identifier = Some(name),
+                    _ => return Err(parser.unexpected_token()),
+                }
+                if !parser.expect(&Token::RightParen) {
+                    return Err(parser.unexpected_token());
+                }
+            }
+            catch_clause = Some((identifier, parser.parse_block()?));
+        }
+        let finally_block = if parser.expect(&Token::Finally) {
+            Some(parser.parse_block()?)
+        } else {
+            None
+        };
+        if catch_clause.is_none() && finally_block.is_none() {
+            return Err(parser.unexpected_token());
+        }
+        Ok(Statement::Try(block, catch_clause, finally_block))
+    }
+}
+
 /// Factory for statement parselets, dispatching based on token type.
 /// 
 /// `parselets` is a HashMap that maps tokens to their corresponding statement parselet,
@@ -144,6 +350,12 @@ pub struct ParseletFactory {
     default: Rc<dyn StatementParselet>
 }
 
+impl Default for ParseletFactory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ParseletFactory {
     pub fn new() -> Self {
         ParseletFactory { 
@@ -159,6 +371,13 @@ impl ParseletFactory {
         map.insert(Token::Return, Rc::new(ReturnParselet));
         map.insert(Token::If, Rc::new(IfParselet));
         map.insert(Token::While, Rc::new(WhileParselet));
+        map.insert(Token::For, Rc::new(ForParselet));
+        map.insert(Token::Try, Rc::new(TryParselet));
+        map.insert(Token::Continue, Rc::new(ContinueParselet));
+        map.insert(Token::Throw, Rc::new(ThrowParselet));
+        map.insert(Token::Switch, Rc::new(SwitchParselet));
+        map.insert(Token::Break, Rc::new(BreakParselet));
+        map.insert(Token::LeftCurlyBrace, Rc::new(BlockParselet));
         map
     }
 
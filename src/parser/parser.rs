@@ -1,7 +1,9 @@
+use std::collections::HashSet;
+
 use crate::{
-    ast::{Block, Expression, Operator, PrefixOperator, Statement},
+    ast::{Block, Expression, Operator, Pattern, PostfixOperator, PrefixOperator, Statement},
     interpreter::errors::{ParserError, ParserErrorKind, SyntaxErrorKind},
-    lexer::Token,
+    lexer::{keyword_name, lexer_error, Token},
     parser::parselets::ParseletFactory,
 };
 
@@ -25,12 +27,14 @@ use crate::{
 /// 4: logical AND
 /// 3: logical OR and nullish coalescing (ie x ?? y)
 /// 2: assignment operations (=, *=, -=, ??=, etc), ternary operator, arrow, yield, spread
-/// 1: comma
+/// 1: comma (sequence, ie a, b, c)
 
 pub struct Parser {
     pub tokens: Vec<Token>,
     position: usize,
     parselet_factory: ParseletFactory,
+    // expression parsing can't fail, so an error found inside an expression waits here for its statement to finish
+    expression_error: Option<ParserError>,
 }
 
 impl Parser {
@@ -38,7 +42,8 @@ impl Parser {
         Parser {
             tokens,
             position: 0,
-            parselet_factory: ParseletFactory::new()
+            parselet_factory: ParseletFactory::new(),
+            expression_error: None,
         }
     }
 
@@ -57,7 +62,7 @@ impl Parser {
         self.tokens.get(position).unwrap_or(&Token::EOF)
     }
 
-    fn peek_keep_white_space(&self) -> &Token {
+    pub(crate) fn peek_keep_white_space(&self) -> &Token {
         self.tokens.get(self.position).unwrap_or(&Token::EOF)
     }
 
@@ -113,7 +118,7 @@ impl Parser {
         let next_token = self.peek_keep_white_space();
         let error = match next_token {
             Token::Ident(name) => SyntaxErrorKind::UnexpectedIdentifier(name.clone()),
-            _ => SyntaxErrorKind::UnexpectedToken(next_token.clone())
+            _ => lexer_error(next_token).unwrap_or_else(|| SyntaxErrorKind::UnexpectedToken(next_token.clone()))
         };
         ParserError {
             kind: ParserErrorKind::SyntaxError(Some(error)),
@@ -123,24 +128,65 @@ impl Parser {
     pub fn parse(&mut self) -> Vec<Result<Statement, ParserError>> {
         let mut statements: Vec<Result<Statement, ParserError>> = vec![];
 
+        let mut declarations = Declarations::default();
         while !matches!(self.peek(), Token::EOF) && self.position < self.tokens.len() {
-            statements.push(self.parse_statement())
+            let statement = match self.parse_statement() {
+                Ok(statement) => declarations.declare(statement),
+                Err(error) => {
+                    self.synchronize();
+                    Err(error)
+                }
+            };
+            statements.push(statement)
         }
         statements
     }
 
+    /// Skips past the rest of a statement that failed to parse, so the
+    /// following statements can still be parsed
+    fn synchronize(&mut self) {
+        // a statement can parse all the way to its semicolon and still be invalid, then there is nothing to skip
+        if self.position > 0
+            && matches!(self.peek_at(self.position - 1), Token::Semicolon | Token::NewLine)
+        {
+            return;
+        }
+        while !matches!(
+            self.peek_keep_white_space(),
+            Token::Semicolon | Token::NewLine | Token::EOF
+        ) {
+            self.position += 1;
+        }
+        if self.peek_keep_white_space() != &Token::EOF {
+            self.position += 1;
+        }
+    }
+
     pub(crate) fn parse_statement(&mut self) -> Result<Statement, ParserError> {
         let token = self.peek().clone();
         let parselet = self.parselet_factory.get_parselet(&token);
-        parselet.parse(self)
+        let statement = parselet.parse(self);
+        match self.expression_error.take() {
+            Some(error) => Err(error),
+            None => statement,
+        }
     }
 
+    /// Parses the parenthesized condition of an `if`, `while` or `switch`.  A bare assignment such as
+    /// `if (x = 5)` is usually a mistyped comparison, so it has to be wrapped again, as in `if ((x = 5))`
     pub(crate) fn parse_paren_wrapped_expression(&mut self) -> Result<Expression, ParserError> {
         if self.expect(&Token::LeftParen) {
+            // checked before parsing, which takes the tokens of nested parens out of the list
+            let wrapped = self.peek() == &Token::LeftParen && self.is_wrapped_in_parens(self.position);
             let conditional_expression = self.parse_expression();
             if !self.expect(&Token::RightParen) {
                 return Err(self.unexpected_token());
             }
+            if matches!(conditional_expression, Expression::Assignment(_, _)) && !wrapped {
+                return Err(ParserError {
+                    kind: ParserErrorKind::SyntaxError(Some(SyntaxErrorKind::AssignmentInCondition)),
+                });
+            }
             return Ok(conditional_expression);
         }
 
@@ -148,24 +194,61 @@ impl Parser {
     }
 
     pub(crate) fn parse_arguments(&mut self) -> Vec<Expression> {
-        let mut arguments = vec![];
+        self.parse_expression_list(&Token::RightParen)
+    }
+
+    /// Parses a function declaration's parameters up to and including the closing paren,
+    /// where every parameter has to be a plain identifier
+    pub(crate) fn parse_parameters(&mut self) -> Result<Vec<Expression>, ParserError> {
+        let mut parameters = vec![];
         while !self.expect(&Token::RightParen) {
-            if self.peek() == &Token::Comma {
-                self.advance();
+            match self.peek().clone() {
+                Token::Ident(name) => {
+                    self.advance();
+                    parameters.push(Expression::Identifier(name));
+                }
+                _ => return Err(self.unexpected_token()),
+            }
+            // each parameter is followed by a comma, or the closing paren, which a trailing comma may also come before
+            if !self.expect(&Token::Comma) && self.peek() != &Token::RightParen {
+                return Err(self.unexpected_token());
+            }
+        }
+        Ok(parameters)
+    }
+
+    /// Parses comma separated expressions up to and including the closing token
+    fn parse_expression_list(&mut self, closing: &Token) -> Vec<Expression> {
+        let mut expressions = vec![];
+        while !self.expect(closing) {
+            // a comma here would leave an empty element, as in `[1,,2]` or `f(,1)`, and arrays with holes aren't supported
+            if matches!(self.peek(), Token::EOF | Token::Comma) {
+                let error = self.unexpected_token();
+                self.report_expression_error(error);
+                break;
+            }
+            let expression = if self.expect_next_n(vec![Token::Dot, Token::Dot, Token::Dot]) {
+                Expression::Spread(Box::new(self.parse_assignment()))
+            } else {
+                self.parse_assignment()
             };
-            let argument = self.parse_expression();
-            // When defining a function's parameters, these should only be Identifiers
-            // But as we are reusing this when we call a function, this is fine
-            // The interpreter is left to decide if a mistake has been made
-            arguments.push(argument)
+            expressions.push(expression);
+            // each element is followed by a comma, or the closing token, so `[1 2]` is an error rather than two elements.
+            // A single trailing comma before the closing token adds nothing
+            if !self.expect(&Token::Comma) && self.peek() != closing {
+                let error = self.unexpected_token();
+                self.report_expression_error(error);
+                break;
+            }
         }
-        arguments
+        expressions
     }
 
     pub(crate) fn parse_block(&mut self) -> Result<Block, ParserError> {
         if self.expect(&Token::LeftCurlyBrace) {
             // building the block
             let mut block_statements = vec![];
+            let mut declarations = Declarations::default();
             while !self.expect(&Token::RightCurlyBrace) {
                 if matches!(
                     self.peek(),
@@ -174,7 +257,9 @@ impl Parser {
                     self.advance();
                     continue;
                 }
-                let statement_result = self.parse_statement();
+                let statement_result = self
+                    .parse_statement()
+                    .and_then(|statement| declarations.declare(statement));
                 match statement_result {
                     Ok(statement) => block_statements.push(statement),
                     Err(error) => return Err(error),
@@ -187,44 +272,98 @@ impl Parser {
     }
 
     pub fn parse_expression(&mut self) -> Expression {
-        self.parse_assignment()
+        self.parse_comma()
     }
 
-    // priority level 2
-    fn parse_assignment(&mut self) -> Expression {
-        let mut expr: Expression = self.parse_logical_or();
-
-        if self.expect_next_n(vec![Token::Star, Token::Equals]) {
-            expr = self.create_operator_and_assign(Operator::Multiply, &mut expr);
+    // priority level 1
+    fn parse_comma(&mut self) -> Expression {
+        let mut expressions = vec![self.parse_assignment()];
+        while self.expect(&Token::Comma) {
+            expressions.push(self.parse_assignment());
+        }
+        if expressions.len() == 1 {
+            return expressions.pop().unwrap();
+        }
+        Expression::Sequence(expressions)
+    }
+
+    /// priority level 2
+    /// Commas separate arguments, elements and properties, so those are parsed from here rather than as a sequence
+    pub(crate) fn parse_assignment(&mut self) -> Expression {
+        let mut expr: Expression = self.parse_conditional();
+
+        if self.expect_next_n(vec![Token::Pipe, Token::Pipe, Token::Equals]) {
+            expr = self.create_logical_assignment(Operator::Or, expr);
+        } else if self.expect_next_n(vec![Token::Ampersand, Token::Ampersand, Token::Equals]) {
+            expr = self.create_logical_assignment(Operator::And, expr);
+        } else if self.expect_next_n(vec![Token::QuestionMark, Token::QuestionMark, Token::Equals]) {
+            expr = self.create_logical_assignment(Operator::NullishCoalescing, expr);
+        } else if self.expect_next_n(vec![Token::Star, Token::Star, Token::Equals]) {
+            expr = self.create_operator_and_assign(Operator::Exponentiation, expr);
+        } else if self.expect_next_n(vec![Token::Star, Token::Equals]) {
+            expr = self.create_operator_and_assign(Operator::Multiply, expr);
         } else if self.expect_next_n(vec![Token::Slash, Token::Equals]) {
-            expr = self.create_operator_and_assign(Operator::Divide, &mut expr);
+            expr = self.create_operator_and_assign(Operator::Divide, expr);
         } else if self.expect_next_n(vec![Token::Plus, Token::Equals]) {
-            expr = self.create_operator_and_assign(Operator::Add, &mut expr);
+            expr = self.create_operator_and_assign(Operator::Add, expr);
         } else if self.expect_next_n(vec![Token::Minus, Token::Equals]) {
-            expr = self.create_operator_and_assign(Operator::Subtract, &mut expr);
+            expr = self.create_operator_and_assign(Operator::Subtract, expr);
+        } else if self.expect_next_n(vec![Token::Percent, Token::Equals]) {
+            expr = self.create_operator_and_assign(Operator::Modulo, expr);
         } else if self.peek() == &Token::Equals && self.peek_at(self.position + 1) != &Token::Equals
         {
             self.advance();
-            let right = self.parse_logical_or();
+            let right = self.parse_conditional();
             expr = Expression::Assignment(Box::new(expr), Box::new(right));
         }
+        if let Expression::Assignment(target, _)
+        | Expression::LogicalAssignment(target, _, _)
+        | Expression::CompoundAssignment(target, _, _) = &expr
+        {
+            self.check_assignment_target(target, SyntaxErrorKind::InvalidAssignmentTarget);
+        }
         expr
     }
 
-    fn create_operator_and_assign(
-        &mut self,
-        operator: Operator,
-        expr: &mut Expression,
-    ) -> Expression {
-        let right = self.parse_logical_or();
-        Expression::Assignment(
-            Box::new(expr.clone()),
-            Box::new(Expression::Operation(
-                Box::new(expr.clone()),
-                operator,
-                Box::new(right),
-            )),
-        )
+    // Only variables, properties and indices can be assigned to or incremented, so `5 = 3` and `++5` are syntax errors
+    fn check_assignment_target(&mut self, target: &Expression, error: SyntaxErrorKind) {
+        if !is_assignable(target) && self.expression_error.is_none() {
+            self.expression_error = Some(ParserError {
+                kind: ParserErrorKind::SyntaxError(Some(error)),
+            });
+        }
+    }
+
+    // Expressions can't return errors, so the first one found is kept for parse_statement to report
+    fn report_expression_error(&mut self, error: ParserError) {
+        if self.expression_error.is_none() {
+            self.expression_error = Some(error);
+        }
+    }
+
+    fn create_logical_assignment(&mut self, operator: Operator, target: Expression) -> Expression {
+        let right = self.parse_assignment();
+        Expression::LogicalAssignment(Box::new(target), operator, Box::new(right))
+    }
+
+    fn create_operator_and_assign(&mut self, operator: Operator, target: Expression) -> Expression {
+        let right = self.parse_conditional();
+        Expression::CompoundAssignment(Box::new(target), operator, Box::new(right))
+    }
+
+    // priority level 2, where both branches may be assignments, so `c ? a = 1 : b = 2` assigns to one of a and b
+    fn parse_conditional(&mut self) -> Expression {
+        let condition = self.parse_logical_or();
+        if self.peek() != &Token::QuestionMark || self.peek_at(self.position + 1) == &Token::QuestionMark {
+            return condition;
+        }
+        self.advance();
+        let consequent = self.parse_assignment();
+        if !self.expect(&Token::Colon) && self.expression_error.is_none() {
+            self.expression_error = Some(self.unexpected_token());
+        }
+        let alternate = self.parse_assignment();
+        Expression::Conditional(Box::new(condition), Box::new(consequent), Box::new(alternate))
     }
 
     fn parse_left_associative<LF, OF>(
@@ -246,11 +385,16 @@ impl Parser {
     // priority level 3
     fn parse_logical_or(&mut self) -> Expression {
         self.parse_left_associative(Parser::parse_logical_and, |parser, left| {
-            if parser.peek() == &Token::Pipe && parser.peek_at(parser.position + 1) == &Token::Pipe {
-                parser.advance();
-                parser.advance();
+            // ||= and ??= are assignments, which parse_assignment handles
+            if parser.peek_at(parser.position + 2) == &Token::Equals {
+                return None;
+            }
+            if parser.expect_next_n(vec![Token::Pipe, Token::Pipe]) {
                 let right = parser.parse_logical_and();
                 Some(Expression::Operation(Box::new(left), Operator::Or, Box::new(right)))
+            } else if parser.expect_next_n(vec![Token::QuestionMark, Token::QuestionMark]) {
+                let right = parser.parse_logical_and();
+                Some(Expression::Operation(Box::new(left), Operator::NullishCoalescing, Box::new(right)))
             } else {
                 None
             }
@@ -262,6 +406,7 @@ impl Parser {
         self.parse_left_associative(Parser::parse_equality, |parser, left| {
             if parser.peek() == &Token::Ampersand
                 && parser.peek_at(parser.position + 1) == &Token::Ampersand
+                && parser.peek_at(parser.position + 2) != &Token::Equals
             {
                 parser.advance();
                 parser.advance();
@@ -294,25 +439,23 @@ impl Parser {
     fn parse_comparator(&mut self) -> Expression {
         self.parse_left_associative(Parser::parse_term, |parser, left| {
             if matches!(parser.peek(), Token::LeftChevron | Token::RightChevron) {
-                let operator = match parser.advance() {
-                    Token::LeftChevron => Operator::LessThan,
-                    Token::RightChevron => Operator::GreaterThan,
+                let include_equality = parser.peek_at(parser.position + 1) == &Token::Equals;
+                let operator = match (parser.advance(), include_equality) {
+                    (Token::LeftChevron, false) => Operator::LessThan,
+                    (Token::RightChevron, false) => Operator::GreaterThan,
+                    (Token::LeftChevron, true) => Operator::LessThanOrEqual,
+                    (Token::RightChevron, true) => Operator::GreaterThanOrEqual,
                     _ => unreachable!(),
                 };
-                let include_equality = parser.expect(&Token::Equals);
-                let right = parser.parse_term();
-                let mut expr =
-                    Expression::Operation(Box::new(left.clone()), operator, Box::new(right.clone()));
                 if include_equality {
-                    let equal_expression =
-                        Expression::Operation(Box::new(left), Operator::Equal, Box::new(right));
-                    expr = Expression::Operation(
-                        Box::new(expr),
-                        Operator::Or,
-                        Box::new(equal_expression),
-                    );
+                    parser.advance();
                 }
-                Some(expr)
+                let right = parser.parse_term();
+                Some(Expression::Operation(Box::new(left), operator, Box::new(right)))
+            } else if parser.peek() == &Token::In {
+                parser.advance();
+                let right = parser.parse_term();
+                Some(Expression::Operation(Box::new(left), Operator::In, Box::new(right)))
             } else {
                 None
             }
@@ -365,8 +508,31 @@ impl Parser {
 
     /// priority level 13
     fn parse_exponentiation(&mut self) -> Expression {
+        // (-2) ** 2 and -2 ** 2 give the same prefix expression, so whether it was wrapped has to be checked here
+        let is_wrapped = self.peek() == &Token::LeftParen;
         self.parse_left_associative(Parser::parse_unary, |parser, left| {
-            if parser.expect_next_n(vec![Token::Star, Token::Star]) {
+            // **= is an assignment, which parse_assignment handles
+            if parser.peek_at(parser.position + 2) != &Token::Equals
+                && parser.expect_next_n(vec![Token::Star, Token::Star])
+            {
+                // -2 ** 2 could mean either order, so JS requires parentheses around one side
+                if !is_wrapped
+                    && matches!(
+                        left,
+                        Expression::Prefix(
+                            PrefixOperator::Negative
+                                | PrefixOperator::Positive
+                                | PrefixOperator::Not
+                                | PrefixOperator::TypeOf
+                                | PrefixOperator::Delete,
+                            _
+                        )
+                    )
+                {
+                    parser.report_expression_error(ParserError {
+                        kind: ParserErrorKind::SyntaxError(Some(SyntaxErrorKind::UnaryBeforeExponentiation)),
+                    });
+                }
                 let right = parser.parse_exponentiation();
                 Some(Expression::Operation(Box::new(left), Operator::Exponentiation, Box::new(right)))
             } else {
@@ -383,6 +549,7 @@ impl Parser {
                 if self.peek() == &token {
                     self.advance();
                     let right = self.parse_unary();
+                    self.check_assignment_target(&right, SyntaxErrorKind::InvalidLeftSidePrefix);
                     match token {
                         Token::Minus => {
                             return Expression::Prefix(PrefixOperator::Decrement, Box::new(right))
@@ -406,12 +573,97 @@ impl Parser {
                 let right = self.parse_unary();
                 Expression::Prefix(PrefixOperator::Not, Box::new(right))
             }
-            _ => self.parse_sub_expression(),
+            Token::TypeOf | Token::Delete => {
+                let operator = match self.advance() {
+                    Token::TypeOf => PrefixOperator::TypeOf,
+                    _ => PrefixOperator::Delete,
+                };
+                let right = self.parse_unary();
+                Expression::Prefix(operator, Box::new(right))
+            }
+            _ => self.parse_postfix(),
+        }
+    }
+
+    /// priority level 15
+    fn parse_postfix(&mut self) -> Expression {
+        let expr = self.parse_call_member();
+        // a ++ on the next line belongs to the next statement, so only look at the rest of this line
+        let operator = match self.peek_keep_white_space() {
+            Token::Plus => PostfixOperator::Increment,
+            Token::Minus => PostfixOperator::Decrement,
+            _ => return expr,
+        };
+        // the lexer doesn't keep spaces, so x + +y and x++ y are the same tokens.  Nothing can follow x++
+        // directly, so ++ before an operand is read as an addition and a unary plus
+        let follows_operand = matches!(
+            self.peek_at(self.position + 2),
+            Token::Ident(_)
+                | Token::Number(_)
+                | Token::Boolean(_)
+                | Token::Null
+                | Token::DoubleQuote
+                | Token::LeftParen
+                | Token::LeftSquareBracket
+                | Token::LeftCurlyBrace
+                | Token::ExclamationMark
+        );
+        // x++ + 1 is a postfix then an addition, but 1 + +-1 can only be an addition as 1 can't be incremented
+        let follows_sign = matches!(self.peek_at(self.position + 2), Token::Plus | Token::Minus);
+        let is_postfix = self.peek_at(self.position + 1) == self.peek_at(self.position)
+            && !follows_operand
+            && (is_assignable(&expr) || !follows_sign);
+        if !is_postfix {
+            return expr;
+        }
+        self.position += 2;
+        self.check_assignment_target(&expr, SyntaxErrorKind::InvalidLeftSidePostfix);
+        Expression::Postfix(Box::new(expr), operator)
+    }
+
+    /// priority level 17
+    /// Member access and calls chain left to right, so a.b() is a call on the member a.b
+    fn parse_call_member(&mut self) -> Expression {
+        let mut expr = self.parse_sub_expression();
+        loop {
+            match self.peek() {
+                Token::LeftParen => {
+                    self.advance(); // get rid of the left paren
+                    let arguments = self.parse_arguments();
+                    expr = Expression::Call(Box::new(expr), arguments);
+                }
+                Token::LeftSquareBracket => {
+                    self.advance(); // get rid of the left square bracket
+                    let index = self.parse_expression();
+                    if !self.expect(&Token::RightSquareBracket) {
+                        let error = self.unexpected_token();
+                        self.report_expression_error(error);
+                    }
+                    expr = Expression::Index(Box::new(expr), Box::new(index));
+                }
+                Token::Dot => match property_name(self.peek_at(self.position + 1)) {
+                    Some(property) => {
+                        self.position += 2;
+                        expr = Expression::Member(Box::new(expr), property);
+                    }
+                    None => {
+                        self.advance(); // get rid of the dot, so the error is about what follows it
+                        let error = self.unexpected_token();
+                        self.report_expression_error(error);
+                        break;
+                    }
+                },
+                _ => break,
+            }
         }
+        expr
     }
 
     /// priority level 18
     fn parse_sub_expression(&mut self) -> Expression {
+        if self.is_arrow_function_start() {
+            return self.parse_arrow_function();
+        }
         let mut sub_level = 0;
         match self.peek() {
             Token::LeftParen => {
@@ -444,21 +696,13 @@ impl Parser {
     fn parse_primary(&mut self) -> Expression {
         match self.advance() {
             Token::Number(n) => Expression::NumberLiteral(n),
-            Token::Ident(name) => {
-                let expr = match self.peek() {
-                    Token::LeftParen => {
-                        self.advance(); // get rid of the left paren
-                        let arguments = self.parse_arguments();
-                        Expression::Call(
-                            Box::new(Expression::Identifier(name.clone())),
-                            arguments,
-                        )
-                    }
-                    _ => Expression::Identifier(name.clone()),
-                };
-                expr
-            }
+            Token::Ident(name) => Expression::Identifier(name),
             Token::Boolean(is_true) => Expression::Boolean(is_true),
+            Token::Null => Expression::Null,
+            Token::LeftSquareBracket => {
+                Expression::Array(self.parse_expression_list(&Token::RightSquareBracket))
+            }
+            Token::LeftCurlyBrace => self.parse_object_literal(),
             Token::DoubleQuote => {
                 let expr = match self.advance() {
                     Token::String(string) => Expression::String(string),
@@ -470,9 +714,280 @@ impl Parser {
                 }
                 expr
             }
+            Token::This => Expression::This,
+            Token::Function => self.parse_function_expression(),
+            token @ (Token::UnterminatedString(_) | Token::Unknown(_) | Token::InvalidIdentifier(_)) => {
+                self.report_expression_error(ParserError {
+                    kind: ParserErrorKind::SyntaxError(lexer_error(&token)),
+                });
+                Expression::Undefined
+            }
             _ => Expression::NumberLiteral(0.0), // fallback
         }
     }
+
+    /// Parses a function expression such as `function (a) { return a; }`, where the name is optional
+    fn parse_function_expression(&mut self) -> Expression {
+        let name = match self.peek().clone() {
+            Token::Ident(name) => {
+                self.advance();
+                name
+            }
+            _ => String::new(),
+        };
+        if !self.expect(&Token::LeftParen) {
+            let error = self.unexpected_token();
+            self.report_expression_error(error);
+            return Expression::Undefined;
+        }
+        let function = self
+            .parse_parameters()
+            .and_then(|parameters| Ok((parameters, self.parse_block()?)));
+        match function {
+            Ok((parameters, block)) => Expression::Function(name, parameters, block),
+            Err(error) => {
+                self.report_expression_error(error);
+                Expression::Undefined
+            }
+        }
+    }
+
+    // An arrow function starts with a single parameter, or a parenthesized list of them, followed by =>
+    fn is_arrow_function_start(&mut self) -> bool {
+        let position = match self.peek() {
+            Token::Ident(_) => self.position,
+            Token::LeftParen => match self.closing_paren_position(self.position) {
+                Some(position) => position,
+                None => return false,
+            },
+            _ => return false,
+        };
+        self.peek_at(position + 1) == &Token::Equals && self.peek_at(position + 2) == &Token::RightChevron
+    }
+
+    // Whether the parens opening at `position` are closed right before the enclosing closing paren
+    fn is_wrapped_in_parens(&self, position: usize) -> bool {
+        let Some(mut position) = self.closing_paren_position(position) else {
+            return false;
+        };
+        position += 1;
+        while self.peek_at(position) == &Token::NewLine {
+            position += 1;
+        }
+        self.peek_at(position) == &Token::RightParen
+    }
+
+    // Finds the paren closing the one at `position`, without consuming anything
+    fn closing_paren_position(&self, mut position: usize) -> Option<usize> {
+        let mut sub_level = 1;
+        while sub_level > 0 {
+            position += 1;
+            match self.peek_at(position) {
+                Token::LeftParen => sub_level += 1,
+                Token::RightParen => sub_level -= 1,
+                Token::EOF => return None,
+                _ => {}
+            }
+        }
+        Some(position)
+    }
+
+    /// Parses an arrow function such as `(a, b) => a + b` or `x => { return x; }`
+    fn parse_arrow_function(&mut self) -> Expression {
+        let parameters = match self.advance() {
+            Token::Ident(name) => Ok(vec![Expression::Identifier(name)]),
+            _ => self.parse_parameters(),
+        };
+        self.position += 2; // get rid of the =>
+        let block = if self.peek() == &Token::LeftCurlyBrace {
+            self.parse_block()
+        } else {
+            let body = self.parse_assignment();
+            Ok(Block::new(vec![Statement::ReturnStatement(Some(body))]))
+        };
+        match parameters.and_then(|parameters| Ok((parameters, block?))) {
+            Ok((parameters, block)) => Expression::ArrowFunction(parameters, block),
+            Err(error) => {
+                self.report_expression_error(error);
+                Expression::Undefined
+            }
+        }
+    }
+
+    /// Parses a destructuring pattern such as `[a, { b, c: [d] }]`, recursing into nested patterns
+    pub(crate) fn parse_pattern(&mut self) -> Result<Pattern, ParserError> {
+        match self.advance() {
+            Token::Ident(name) => Ok(Pattern::Identifier(name)),
+            Token::LeftSquareBracket => {
+                let mut elements = vec![];
+                while !self.expect(&Token::RightSquareBracket) {
+                    if self.peek() == &Token::Comma {
+                        self.advance();
+                        continue;
+                    }
+                    if self.peek() == &Token::EOF {
+                        return Err(self.unexpected_token());
+                    }
+                    elements.push(self.parse_pattern()?);
+                }
+                Ok(Pattern::Array(elements))
+            }
+            Token::LeftCurlyBrace => {
+                let mut properties = vec![];
+                while !self.expect(&Token::RightCurlyBrace) {
+                    if self.peek() == &Token::Comma {
+                        self.advance();
+                        continue;
+                    }
+                    let token = self.peek().clone();
+                    let key = match property_name(&token) {
+                        Some(name) if matches!(token, Token::Ident(_)) || self.peek_at(self.position + 1) == &Token::Colon => {
+                            self.advance();
+                            name
+                        }
+                        _ => return Err(self.unexpected_token()),
+                    };
+                    let pattern = if self.expect(&Token::Colon) {
+                        self.parse_pattern()?
+                    } else {
+                        Pattern::Identifier(key.clone())
+                    };
+                    properties.push((key, pattern));
+                }
+                Ok(Pattern::Object(properties))
+            }
+            _ => {
+                self.position -= 1;
+                Err(self.unexpected_token())
+            }
+        }
+    }
+
+    // The opening curly brace has already been consumed
+    fn parse_object_literal(&mut self) -> Expression {
+        let mut properties = vec![];
+        while !self.expect(&Token::RightCurlyBrace) {
+            let key = match self.peek().clone() {
+                Token::Number(number) => {
+                    self.advance();
+                    number.to_string()
+                }
+                Token::DoubleQuote => {
+                    self.advance();
+                    let key = match self.advance() {
+                        Token::String(string) => string,
+                        _ => String::new(),
+                    };
+                    self.expect(&Token::DoubleQuote);
+                    key
+                }
+                token => match property_name(&token) {
+                    // a keyword can name a property, but not stand in for a variable the way { a } does
+                    Some(name)
+                        if matches!(token, Token::Ident(_))
+                            || matches!(self.peek_at(self.position + 1), Token::Colon | Token::LeftParen) =>
+                    {
+                        self.advance();
+                        name
+                    }
+                    // this is also where a missing closing brace is reported, once the input runs out
+                    _ => {
+                        let error = self.unexpected_token();
+                        self.report_expression_error(error);
+                        break;
+                    }
+                },
+            };
+            // shorthand properties like { a } take their value from the variable of the same name
+            let value = if self.expect(&Token::Colon) {
+                self.parse_assignment()
+            } else if self.expect(&Token::LeftParen) {
+                // methods like { get() { ... } } are functions named after their key
+                let method = self
+                    .parse_parameters()
+                    .and_then(|parameters| Ok((parameters, self.parse_block()?)));
+                match method {
+                    Ok((parameters, block)) => Expression::Function(key.clone(), parameters, block),
+                    Err(error) => {
+                        self.report_expression_error(error);
+                        break;
+                    }
+                }
+            } else {
+                Expression::Identifier(key.clone())
+            };
+            properties.push((key, value));
+            // each property is followed by a comma, or the closing brace, which a trailing comma may also come before
+            if !self.expect(&Token::Comma) && self.peek() != &Token::RightCurlyBrace {
+                let error = self.unexpected_token();
+                self.report_expression_error(error);
+                break;
+            }
+        }
+        Expression::Object(properties)
+    }
+}
+
+/// The name a token gives a property after a `.` or as an object key, where keywords are allowed too
+fn property_name(token: &Token) -> Option<String> {
+    match token {
+        Token::Ident(name) => Some(name.clone()),
+        _ => keyword_name(token).map(String::from),
+    }
+}
+
+fn is_assignable(expression: &Expression) -> bool {
+    matches!(
+        expression,
+        Expression::Identifier(_) | Expression::Member(_, _) | Expression::Index(_, _)
+    )
+}
+
+/// The names declared so far in one scope, used to reject a `let` that reuses a name.
+/// Declaring the same function twice is allowed, with the last declaration winning.
+#[derive(Default)]
+struct Declarations {
+    variables: HashSet<String>,
+    functions: HashSet<String>,
+}
+
+impl Declarations {
+    fn declare(&mut self, statement: Statement) -> Result<Statement, ParserError> {
+        let (names, is_function) = match &statement {
+            Statement::Let(identifier, _) => (vec![identifier.clone()], false),
+            Statement::LetPattern(pattern, _) => (pattern_identifiers(pattern), false),
+            Statement::FunctionDeclaration(identifier, _, _) => (vec![identifier.clone()], true),
+            _ => (vec![], false),
+        };
+        for name in names {
+            let already_declared = self.variables.contains(&name)
+                || (!is_function && self.functions.contains(&name));
+            if already_declared {
+                return Err(ParserError {
+                    kind: ParserErrorKind::SyntaxError(Some(
+                        SyntaxErrorKind::IdentifierAlreadyDeclared(name),
+                    )),
+                });
+            }
+            if is_function {
+                self.functions.insert(name);
+            } else {
+                self.variables.insert(name);
+            }
+        }
+        Ok(statement)
+    }
+}
+
+fn pattern_identifiers(pattern: &Pattern) -> Vec<String> {
+    match pattern {
+        Pattern::Identifier(identifier) => vec![identifier.clone()],
+        Pattern::Array(elements) => elements.iter().flat_map(pattern_identifiers).collect(),
+        Pattern::Object(properties) => properties
+            .iter()
+            .flat_map(|(_, pattern)| pattern_identifiers(pattern))
+            .collect(),
+    }
 }
 
 pub fn separate_out_statements_and_parser_errors(
@@ -492,6 +1007,7 @@ pub fn separate_out_statements_and_parser_errors(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::lexer::tokenize;
 
     #[test]
     fn it_should_interpret_numbers_as_number_expressions() {
@@ -746,6 +1262,69 @@ mod tests {
         assert_eq!(result[0], Ok(expected));
     }
 
+    #[test]
+    fn it_should_handle_less_than_or_equal() {
+        let tokens = vec![
+            Token::Number(1.0),
+            Token::LeftChevron,
+            Token::Equals,
+            Token::Number(2.0),
+            Token::EOF,
+        ];
+        let mut parser = Parser::new(tokens);
+        let result = parser.parse();
+        let expected = Statement::ExpressionStatement(Expression::Operation(
+            Box::new(Expression::NumberLiteral(1.0)),
+            Operator::LessThanOrEqual,
+            Box::new(Expression::NumberLiteral(2.0)),
+        ));
+        assert_eq!(result[0], Ok(expected));
+    }
+
+    #[test]
+    fn it_should_parse_postfix_increment_but_not_unary_plus_after_addition() {
+        let mut parser = Parser::new(tokenize("x++; x + +y;"));
+        let result = parser.parse();
+        assert_eq!(
+            result[0],
+            Ok(Statement::ExpressionStatement(Expression::Postfix(
+                Box::new(Expression::Identifier("x".to_string())),
+                PostfixOperator::Increment,
+            )))
+        );
+        assert_eq!(
+            result[1],
+            Ok(Statement::ExpressionStatement(Expression::Operation(
+                Box::new(Expression::Identifier("x".to_string())),
+                Operator::Add,
+                Box::new(Expression::Prefix(
+                    PrefixOperator::Positive,
+                    Box::new(Expression::Identifier("y".to_string())),
+                )),
+            )))
+        );
+    }
+
+    #[test]
+    fn it_should_parse_postfix_increment_followed_by_addition_or_subtraction() {
+        let mut parser = Parser::new(tokenize("x++ + 1; x++ - 1;"));
+        let result = parser.parse();
+        let increment = Box::new(Expression::Postfix(
+            Box::new(Expression::Identifier("x".to_string())),
+            PostfixOperator::Increment,
+        ));
+        for (statement, operator) in result.into_iter().zip([Operator::Add, Operator::Subtract]) {
+            assert_eq!(
+                statement,
+                Ok(Statement::ExpressionStatement(Expression::Operation(
+                    increment.clone(),
+                    operator,
+                    Box::new(Expression::NumberLiteral(1.0)),
+                )))
+            );
+        }
+    }
+
     #[test]
     fn it_should_handle_double_equals() {
         let tokens = vec![
@@ -836,24 +1415,24 @@ mod tests {
 
     #[test]
     fn it_should_handle_double_plus_as_prefix() {
-        let tokens = vec![Token::Plus, Token::Plus, Token::Number(0.0)];
+        let tokens = vec![Token::Plus, Token::Plus, Token::Ident("x".into())];
         let mut parser = Parser::new(tokens);
         let result = parser.parse();
         let expected = Statement::ExpressionStatement(Expression::Prefix(
             PrefixOperator::Increment,
-            Box::new(Expression::NumberLiteral(0.0)),
+            Box::new(Expression::Identifier("x".into())),
         ));
         assert_eq!(result[0], Ok(expected));
     }
 
     #[test]
     fn it_should_handle_double_minus_as_prefix() {
-        let tokens = vec![Token::Minus, Token::Minus, Token::Number(4.0)];
+        let tokens = vec![Token::Minus, Token::Minus, Token::Ident("x".into())];
         let mut parser = Parser::new(tokens);
         let result = parser.parse();
         let expected = Statement::ExpressionStatement(Expression::Prefix(
             PrefixOperator::Decrement,
-            Box::new(Expression::NumberLiteral(4.0)),
+            Box::new(Expression::Identifier("x".into())),
         ));
         assert_eq!(result[0], Ok(expected));
     }
@@ -1081,13 +1660,10 @@ mod tests {
 
         assert_eq!(result.len(), 2);
 
-        let expected = Statement::ExpressionStatement(Expression::Assignment(
+        let expected = Statement::ExpressionStatement(Expression::CompoundAssignment(
                 Box::new(Expression::Identifier("x".into())),
-                Box::new(Expression::Operation(
-                    Box::new(Expression::Identifier("x".into())),
-                    Operator::Multiply,
-                    Box::new(Expression::NumberLiteral(4.0))
-                ))
+                Operator::Multiply,
+                Box::new(Expression::NumberLiteral(4.0))
             ));
 
         assert_eq!(
@@ -1115,13 +1691,10 @@ mod tests {
 
         assert_eq!(result.len(), 2);
 
-        let expected = Statement::ExpressionStatement(Expression::Assignment(
+        let expected = Statement::ExpressionStatement(Expression::CompoundAssignment(
                 Box::new(Expression::Identifier("x".into())),
-                Box::new(Expression::Operation(
-                    Box::new(Expression::Identifier("x".into())),
-                    Operator::Divide,
-                    Box::new(Expression::NumberLiteral(4.0))
-                ))
+                Operator::Divide,
+                Box::new(Expression::NumberLiteral(4.0))
             ));
 
         assert_eq!(
@@ -1149,13 +1722,10 @@ mod tests {
 
         assert_eq!(result.len(), 2);
 
-        let expected = Statement::ExpressionStatement(Expression::Assignment(
+        let expected = Statement::ExpressionStatement(Expression::CompoundAssignment(
                 Box::new(Expression::Identifier("x".into())),
-                Box::new(Expression::Operation(
-                    Box::new(Expression::Identifier("x".into())),
-                    Operator::Add,
-                    Box::new(Expression::NumberLiteral(4.0))
-                ))
+                Operator::Add,
+                Box::new(Expression::NumberLiteral(4.0))
             ));
 
         assert_eq!(
@@ -1183,13 +1753,10 @@ mod tests {
 
         assert_eq!(result.len(), 2);
 
-        let expected = Statement::ExpressionStatement(Expression::Assignment(
+        let expected = Statement::ExpressionStatement(Expression::CompoundAssignment(
                 Box::new(Expression::Identifier("x".into())),
-                Box::new(Expression::Operation(
-                    Box::new(Expression::Identifier("x".into())),
-                    Operator::Subtract,
-                    Box::new(Expression::NumberLiteral(4.0))
-                ))
+                Operator::Subtract,
+                Box::new(Expression::NumberLiteral(4.0))
             ));
 
         assert_eq!(
@@ -1198,6 +1765,55 @@ mod tests {
         )
     }
 
+    #[test]
+    fn it_should_handle_percent_equals() {
+        let tokens = vec![
+            Token::Ident("x".into()),
+            Token::Percent,
+            Token::Equals,
+            Token::Number(3.0),
+            Token::Semicolon,
+        ];
+        let mut parser = Parser::new(tokens);
+        let result = parser.parse();
+
+        let expected = Statement::ExpressionStatement(Expression::CompoundAssignment(
+                Box::new(Expression::Identifier("x".into())),
+                Operator::Modulo,
+                Box::new(Expression::NumberLiteral(3.0))
+            ));
+
+        assert_eq!(
+            result[0],
+            Ok(expected)
+        )
+    }
+
+    #[test]
+    fn it_should_handle_star_star_equals() {
+        let tokens = vec![
+            Token::Ident("x".into()),
+            Token::Star,
+            Token::Star,
+            Token::Equals,
+            Token::Number(3.0),
+            Token::Semicolon,
+        ];
+        let mut parser = Parser::new(tokens);
+        let result = parser.parse();
+
+        let expected = Statement::ExpressionStatement(Expression::CompoundAssignment(
+                Box::new(Expression::Identifier("x".into())),
+                Operator::Exponentiation,
+                Box::new(Expression::NumberLiteral(3.0))
+            ));
+
+        assert_eq!(
+            result[0],
+            Ok(expected)
+        )
+    }
+
     #[test]
     fn it_should_handle_else() {
         let tokens = vec![
@@ -1293,27 +1909,403 @@ mod tests {
     }
 
     #[test]
-    fn it_should_throw_parser_error_missing_right_paren() {
+    fn it_should_parse_array_literal() {
         let tokens = vec![
-            Token::If,
-            Token::LeftParen,
-            Token::Ident("x".into()),
-            Token::LeftCurlyBrace,
+            Token::LeftSquareBracket,
+            Token::Number(1.0),
+            Token::Comma,
             Token::Ident("x".into()),
-            Token::RightCurlyBrace,
+            Token::RightSquareBracket,
         ];
 
         let mut parser = Parser::new(tokens);
         let result = parser.parse();
-        assert_eq!(result[0], Err(ParserError { kind: ParserErrorKind::SyntaxError(Some(SyntaxErrorKind::UnexpectedToken(Token::LeftCurlyBrace)))}))
+
+        let expected = Statement::ExpressionStatement(Expression::Array(vec![
+            Expression::NumberLiteral(1.0),
+            Expression::Identifier("x".into()),
+        ]));
+
+        assert_eq!(result[0], Ok(expected));
     }
 
     #[test]
-    fn it_should_throw_parser_error_missing_right_curly_brace() {
+    fn it_should_parse_spread_in_call_arguments() {
         let tokens = vec![
-            Token::If,
+            Token::Ident("f".into()),
             Token::LeftParen,
-            Token::Ident("x".into()),
+            Token::Number(1.0),
+            Token::Comma,
+            Token::Dot,
+            Token::Dot,
+            Token::Dot,
+            Token::Ident("arr".into()),
+            Token::Comma,
+            Token::Number(2.0),
+            Token::RightParen,
+        ];
+
+        let mut parser = Parser::new(tokens);
+        let result = parser.parse();
+
+        let expected = Statement::ExpressionStatement(Expression::Call(
+            Box::new(Expression::Identifier("f".into())),
+            vec![
+                Expression::NumberLiteral(1.0),
+                Expression::Spread(Box::new(Expression::Identifier("arr".into()))),
+                Expression::NumberLiteral(2.0),
+            ],
+        ));
+
+        assert_eq!(result[0], Ok(expected));
+    }
+
+    #[test]
+    fn it_should_parse_method_call_then_member_access() {
+        let tokens = vec![
+            Token::Ident("text".into()),
+            Token::Dot,
+            Token::Ident("split".into()),
+            Token::LeftParen,
+            Token::DoubleQuote,
+            Token::String(",".into()),
+            Token::DoubleQuote,
+            Token::RightParen,
+            Token::Dot,
+            Token::Ident("length".into()),
+        ];
+
+        let mut parser = Parser::new(tokens);
+        let result = parser.parse();
+
+        let expected = Statement::ExpressionStatement(Expression::Member(
+            Box::new(Expression::Call(
+                Box::new(Expression::Member(
+                    Box::new(Expression::Identifier("text".into())),
+                    "split".into(),
+                )),
+                vec![Expression::String(",".into())],
+            )),
+            "length".into(),
+        ));
+
+        assert_eq!(result[0], Ok(expected));
+    }
+
+    #[test]
+    fn it_should_parse_a_call_on_two_levels_of_member_access() {
+        let mut parser = Parser::new(tokenize("a.b.c();"));
+        let result = parser.parse();
+
+        let expected = Statement::ExpressionStatement(Expression::Call(
+            Box::new(Expression::Member(
+                Box::new(Expression::Member(Box::new(Expression::Identifier("a".into())), "b".into())),
+                "c".into(),
+            )),
+            vec![],
+        ));
+
+        assert_eq!(result[0], Ok(expected));
+    }
+
+    #[test]
+    fn it_should_parse_member_access_on_an_index() {
+        let mut parser = Parser::new(tokenize("arr[0].length;"));
+        let result = parser.parse();
+
+        let expected = Statement::ExpressionStatement(Expression::Member(
+            Box::new(Expression::Index(
+                Box::new(Expression::Identifier("arr".into())),
+                Box::new(Expression::NumberLiteral(0.0)),
+            )),
+            "length".into(),
+        ));
+
+        assert_eq!(result[0], Ok(expected));
+    }
+
+    #[test]
+    fn it_should_parse_index_access() {
+        let tokens = vec![
+            Token::DoubleQuote,
+            Token::String("hello".into()),
+            Token::DoubleQuote,
+            Token::LeftSquareBracket,
+            Token::Number(1.0),
+            Token::RightSquareBracket,
+        ];
+
+        let mut parser = Parser::new(tokens);
+        let result = parser.parse();
+
+        let expected = Statement::ExpressionStatement(Expression::Index(
+            Box::new(Expression::String("hello".into())),
+            Box::new(Expression::NumberLiteral(1.0)),
+        ));
+
+        assert_eq!(result[0], Ok(expected));
+    }
+
+    #[test]
+    fn it_should_parse_object_literal() {
+        let tokens = vec![
+            Token::LeftParen,
+            Token::LeftCurlyBrace,
+            Token::Ident("a".into()),
+            Token::Colon,
+            Token::Number(1.0),
+            Token::Comma,
+            Token::DoubleQuote,
+            Token::String("b".into()),
+            Token::DoubleQuote,
+            Token::Colon,
+            Token::Boolean(true),
+            Token::RightCurlyBrace,
+            Token::RightParen,
+        ];
+
+        let mut parser = Parser::new(tokens);
+        let result = parser.parse();
+
+        let expected = Statement::ExpressionStatement(Expression::Object(vec![
+            ("a".into(), Expression::NumberLiteral(1.0)),
+            ("b".into(), Expression::Boolean(true)),
+        ]));
+
+        assert_eq!(result[0], Ok(expected));
+    }
+
+    #[test]
+    fn it_should_parse_nested_destructuring_pattern() {
+        let tokens = tokenize("let [[a], { b, c: [d] }] = x;");
+
+        let mut parser = Parser::new(tokens);
+        let result = parser.parse();
+
+        let expected = Statement::LetPattern(
+            Pattern::Array(vec![
+                Pattern::Array(vec![Pattern::Identifier("a".into())]),
+                Pattern::Object(vec![
+                    ("b".into(), Pattern::Identifier("b".into())),
+                    ("c".into(), Pattern::Array(vec![Pattern::Identifier("d".into())])),
+                ]),
+            ]),
+            Expression::Identifier("x".into()),
+        );
+
+        assert_eq!(result[0], Ok(expected));
+    }
+
+    fn redeclaration_error(identifier: &str) -> ParserError {
+        ParserError {
+            kind: ParserErrorKind::SyntaxError(Some(SyntaxErrorKind::IdentifierAlreadyDeclared(
+                identifier.into(),
+            ))),
+        }
+    }
+
+    #[test]
+    fn it_should_reject_function_declared_with_the_name_of_a_let() {
+        let mut parser = Parser::new(tokenize("let f = 1; function f() {}"));
+        let result = parser.parse();
+
+        assert_eq!(result[1], Err(redeclaration_error("f")));
+    }
+
+    #[test]
+    fn it_should_reject_let_declared_with_the_name_of_a_function() {
+        let mut parser = Parser::new(tokenize("function f() {} let f = 1;"));
+        let result = parser.parse();
+
+        assert_eq!(result[1], Err(redeclaration_error("f")));
+    }
+
+    #[test]
+    fn it_should_reject_redeclared_let_inside_a_block() {
+        let mut parser = Parser::new(tokenize("function g() { let [a] = x; let a = 1; }"));
+        let result = parser.parse();
+
+        assert_eq!(result[0], Err(redeclaration_error("a")));
+    }
+
+    #[test]
+    fn it_should_allow_the_same_name_in_a_nested_scope() {
+        let mut parser = Parser::new(tokenize("let a = 1; if (a) { let a = 2; }"));
+        let result = parser.parse();
+
+        assert!(result.iter().all(|statement| statement.is_ok()));
+    }
+
+    #[test]
+    fn it_should_parse_leading_curly_brace_as_block() {
+        let tokens = vec![Token::LeftCurlyBrace, Token::RightCurlyBrace];
+
+        let mut parser = Parser::new(tokens);
+        let result = parser.parse();
+
+        assert_eq!(result[0], Ok(Statement::Block(Block::new(vec![]))));
+    }
+
+    #[test]
+    fn it_should_parse_parenthesized_curly_braces_as_object_literal() {
+        let tokens = tokenize("({});");
+
+        let mut parser = Parser::new(tokens);
+        let result = parser.parse();
+
+        assert_eq!(result[0], Ok(Statement::ExpressionStatement(Expression::Object(vec![]))));
+    }
+
+    #[test]
+    fn it_should_parse_a_ternary_below_logical_or() {
+        let mut parser = Parser::new(tokenize("a || b ? c : d;"));
+        let result = parser.parse();
+
+        let expected = Expression::Conditional(
+            Box::new(Expression::Operation(
+                Box::new(Expression::Identifier("a".into())),
+                Operator::Or,
+                Box::new(Expression::Identifier("b".into())),
+            )),
+            Box::new(Expression::Identifier("c".into())),
+            Box::new(Expression::Identifier("d".into())),
+        );
+        assert_eq!(result[0], Ok(Statement::ExpressionStatement(expected)));
+    }
+
+    #[test]
+    fn it_should_reject_a_ternary_without_a_colon() {
+        let mut parser = Parser::new(tokenize("a ? b;"));
+        let result = parser.parse();
+
+        assert!(result[0].is_err());
+    }
+
+    #[test]
+    fn it_should_reject_try_without_catch_or_finally() {
+        let mut parser = Parser::new(tokenize("try { f(); } g();"));
+        let result = parser.parse();
+
+        assert!(result[0].is_err());
+    }
+
+    #[test]
+    fn it_should_parse_for_of() {
+        let tokens = vec![
+            Token::For,
+            Token::LeftParen,
+            Token::Let,
+            Token::Ident("x".into()),
+            Token::Ident("of".into()),
+            Token::Ident("items".into()),
+            Token::RightParen,
+            Token::LeftCurlyBrace,
+            Token::Ident("x".into()),
+            Token::RightCurlyBrace,
+        ];
+
+        let mut parser = Parser::new(tokens);
+        let result = parser.parse();
+
+        let expected = Statement::ForOf(
+            "x".into(),
+            Expression::Identifier("items".into()),
+            Block::new(vec![Statement::ExpressionStatement(Expression::Identifier(
+                "x".into(),
+            ))]),
+        );
+
+        assert_eq!(result[0], Ok(expected));
+    }
+
+    #[test]
+    fn it_should_parse_logical_assignment() {
+        let tokens = tokenize("x ||= y ?? 5;");
+
+        let mut parser = Parser::new(tokens);
+        let result = parser.parse();
+
+        let expected = Statement::ExpressionStatement(Expression::LogicalAssignment(
+            Box::new(Expression::Identifier("x".into())),
+            Operator::Or,
+            Box::new(Expression::Operation(
+                Box::new(Expression::Identifier("y".into())),
+                Operator::NullishCoalescing,
+                Box::new(Expression::NumberLiteral(5.0)),
+            )),
+        ));
+
+        assert_eq!(result[0], Ok(expected));
+    }
+
+    #[test]
+    fn it_should_parse_for_with_empty_sections() {
+        let tokens = vec![
+            Token::For,
+            Token::LeftParen,
+            Token::Semicolon,
+            Token::Semicolon,
+            Token::RightParen,
+            Token::LeftCurlyBrace,
+            Token::Break,
+            Token::Semicolon,
+            Token::RightCurlyBrace,
+        ];
+
+        let mut parser = Parser::new(tokens);
+        let result = parser.parse();
+
+        let expected = Statement::For(None, None, None, Block::new(vec![Statement::Break]));
+
+        assert_eq!(result[0], Ok(expected));
+    }
+
+    #[test]
+    fn it_should_parse_for_with_every_section() {
+        let tokens = tokenize("for (let i = 0; i < 3; ++i) { i; }");
+
+        let mut parser = Parser::new(tokens);
+        let result = parser.parse();
+
+        let expected = Statement::For(
+            Some(Box::new(Statement::Let("i".into(), Expression::NumberLiteral(0.0)))),
+            Some(Expression::Operation(
+                Box::new(Expression::Identifier("i".into())),
+                Operator::LessThan,
+                Box::new(Expression::NumberLiteral(3.0)),
+            )),
+            Some(Expression::Prefix(
+                PrefixOperator::Increment,
+                Box::new(Expression::Identifier("i".into())),
+            )),
+            Block::new(vec![Statement::ExpressionStatement(Expression::Identifier("i".into()))]),
+        );
+
+        assert_eq!(result[0], Ok(expected));
+    }
+
+    #[test]
+    fn it_should_throw_parser_error_missing_right_paren() {
+        let tokens = vec![
+            Token::If,
+            Token::LeftParen,
+            Token::Ident("x".into()),
+            Token::LeftCurlyBrace,
+            Token::Ident("x".into()),
+            Token::RightCurlyBrace,
+        ];
+
+        let mut parser = Parser::new(tokens);
+        let result = parser.parse();
+        assert_eq!(result[0], Err(ParserError { kind: ParserErrorKind::SyntaxError(Some(SyntaxErrorKind::UnexpectedToken(Token::LeftCurlyBrace)))}))
+    }
+
+    #[test]
+    fn it_should_throw_parser_error_missing_right_curly_brace() {
+        let tokens = vec![
+            Token::If,
+            Token::LeftParen,
+            Token::Ident("x".into()),
             Token::RightParen,
             Token::LeftCurlyBrace,
             Token::Ident("x".into()),
@@ -1323,4 +2315,330 @@ mod tests {
         let result = parser.parse();
         assert_eq!(result[0], Err(ParserError { kind: ParserErrorKind::SyntaxError(Some(SyntaxErrorKind::UnexpectedToken(Token::EOF)))}))
     }
+
+    #[test]
+    fn it_should_keep_parsing_after_a_malformed_statement() {
+        let mut parser = Parser::new(tokenize("let = ;\nlet y = 2;"));
+        let result = parser.parse();
+        assert_eq!(result.len(), 2);
+        assert!(result[0].is_err());
+        assert_eq!(
+            result[1],
+            Ok(Statement::Let("y".to_string(), Expression::NumberLiteral(2.0)))
+        );
+    }
+
+    #[test]
+    fn it_should_end_statements_at_a_line_break_without_semicolons() {
+        let mut parser = Parser::new(tokenize("let x = 1\nlet y = x + 1\n"));
+        let result = parser.parse();
+        assert_eq!(
+            result,
+            vec![
+                Ok(Statement::Let("x".to_string(), Expression::NumberLiteral(1.0))),
+                Ok(Statement::Let(
+                    "y".to_string(),
+                    Expression::Operation(
+                        Box::new(Expression::Identifier("x".to_string())),
+                        Operator::Add,
+                        Box::new(Expression::NumberLiteral(1.0)),
+                    )
+                )),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_should_end_a_return_at_a_line_break() {
+        let mut parser = Parser::new(tokenize("return\n5"));
+        let result = parser.parse();
+        assert_eq!(
+            result,
+            vec![
+                Ok(Statement::ReturnStatement(None)),
+                Ok(Statement::ExpressionStatement(Expression::NumberLiteral(5.0))),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_should_allow_a_trailing_comma_in_arguments_and_arrays() {
+        let mut parser = Parser::new(tokenize("f(1, 2,); [1, 2,];"));
+        let result = parser.parse();
+        let numbers = vec![Expression::NumberLiteral(1.0), Expression::NumberLiteral(2.0)];
+        assert_eq!(
+            result,
+            vec![
+                Ok(Statement::ExpressionStatement(Expression::Call(
+                    Box::new(Expression::Identifier("f".to_string())),
+                    numbers.clone(),
+                ))),
+                Ok(Statement::ExpressionStatement(Expression::Array(numbers))),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_should_reject_parameters_that_are_not_identifiers() {
+        let mut parser = Parser::new(tokenize("function f(1, 2) {}"));
+        let result = parser.parse();
+        assert_eq!(
+            result[0],
+            Err(ParserError {
+                kind: ParserErrorKind::SyntaxError(Some(SyntaxErrorKind::UnexpectedToken(Token::Number(1.0)))),
+            })
+        );
+    }
+
+    #[test]
+    fn it_should_parse_identifier_parameters_with_a_trailing_comma() {
+        let mut parser = Parser::new(tokenize("function f(a, b,) {}"));
+        let result = parser.parse();
+        assert_eq!(
+            result[0],
+            Ok(Statement::FunctionDeclaration(
+                "f".to_string(),
+                vec![Expression::Identifier("a".to_string()), Expression::Identifier("b".to_string())],
+                Block::new(vec![]),
+            ))
+        );
+    }
+
+    #[test]
+    fn it_should_reject_assigning_to_a_literal() {
+        let mut parser = Parser::new(tokenize("5 = 3; 5 += 3; let x = 1;"));
+        let result = parser.parse();
+        let invalid_target = Err(ParserError {
+            kind: ParserErrorKind::SyntaxError(Some(SyntaxErrorKind::InvalidAssignmentTarget)),
+        });
+        assert_eq!(result[0], invalid_target);
+        assert_eq!(result[1], invalid_target);
+        assert_eq!(result[2], Ok(Statement::Let("x".to_string(), Expression::NumberLiteral(1.0))));
+    }
+
+    #[test]
+    fn it_should_parse_arrow_functions() {
+        let mut parser = Parser::new(tokenize("x => x * 2; (a, b) => { return a; };"));
+        let result = parser.parse();
+
+        let doubled = Expression::Operation(
+            Box::new(Expression::Identifier("x".into())),
+            Operator::Multiply,
+            Box::new(Expression::NumberLiteral(2.0)),
+        );
+        assert_eq!(
+            result[0],
+            Ok(Statement::ExpressionStatement(Expression::ArrowFunction(
+                vec![Expression::Identifier("x".into())],
+                Block::new(vec![Statement::ReturnStatement(Some(doubled))]),
+            )))
+        );
+        assert_eq!(
+            result[1],
+            Ok(Statement::ExpressionStatement(Expression::ArrowFunction(
+                vec![Expression::Identifier("a".into()), Expression::Identifier("b".into())],
+                Block::new(vec![Statement::ReturnStatement(Some(Expression::Identifier("a".into())))]),
+            )))
+        );
+    }
+
+    #[test]
+    fn it_should_parse_anonymous_function_expressions() {
+        let mut parser = Parser::new(tokenize("let f = function(b) { return b; };"));
+        let result = parser.parse();
+
+        assert_eq!(
+            result[0],
+            Ok(Statement::Let(
+                "f".into(),
+                Expression::Function(
+                    String::new(),
+                    vec![Expression::Identifier("b".into())],
+                    Block::new(vec![Statement::ReturnStatement(Some(Expression::Identifier("b".into())))]),
+                )
+            ))
+        );
+    }
+
+    #[test]
+    fn it_should_parse_commas_as_a_sequence_only_outside_lists() {
+        let mut parser = Parser::new(tokenize("a, b; f(a, b);"));
+        let result = parser.parse();
+
+        assert_eq!(
+            result[0],
+            Ok(Statement::ExpressionStatement(Expression::Sequence(vec![
+                Expression::Identifier("a".into()),
+                Expression::Identifier("b".into()),
+            ])))
+        );
+        assert_eq!(
+            result[1],
+            Ok(Statement::ExpressionStatement(Expression::Call(
+                Box::new(Expression::Identifier("f".into())),
+                vec![Expression::Identifier("a".into()), Expression::Identifier("b".into())],
+            )))
+        );
+    }
+
+    #[test]
+    fn it_should_reject_a_unary_operator_directly_before_exponentiation() {
+        let mut parser = Parser::new(tokenize("-2 ** 2; (-2) ** 2; -(2 ** 2); --x ** 2;"));
+        let result = parser.parse();
+
+        assert_eq!(
+            result[0],
+            Err(ParserError {
+                kind: ParserErrorKind::SyntaxError(Some(SyntaxErrorKind::UnaryBeforeExponentiation)),
+            })
+        );
+        assert!(result[1].is_ok());
+        assert!(result[2].is_ok());
+        assert!(result[3].is_ok());
+    }
+
+    #[test]
+    fn it_should_parse_switch_cases_and_default() {
+        let mut parser = Parser::new(tokenize("switch (x) { case 1: f(); break; default: g(); }"));
+        let result = parser.parse();
+
+        let call = |name: &str| {
+            Statement::ExpressionStatement(Expression::Call(Box::new(Expression::Identifier(name.into())), vec![]))
+        };
+        assert_eq!(
+            result[0],
+            Ok(Statement::Switch(
+                Expression::Identifier("x".into()),
+                vec![
+                    (Some(Expression::NumberLiteral(1.0)), vec![call("f"), Statement::Break]),
+                    (None, vec![call("g")]),
+                ],
+            ))
+        );
+    }
+
+    #[test]
+    fn it_should_reject_assignment_as_a_condition_unless_wrapped_again() {
+        let parse_first = |source: &str| Parser::new(tokenize(source)).parse().remove(0);
+        let assignment_in_condition = Err(ParserError {
+            kind: ParserErrorKind::SyntaxError(Some(SyntaxErrorKind::AssignmentInCondition)),
+        });
+        let assignment = Expression::Assignment(
+            Box::new(Expression::Identifier("x".into())),
+            Box::new(Expression::NumberLiteral(5.0)),
+        );
+
+        assert!(parse_first("if (x == 5) {}").is_ok());
+        assert_eq!(parse_first("if (x = 5) {}"), assignment_in_condition);
+        assert_eq!(parse_first("while (x = 5) {}"), assignment_in_condition);
+        assert_eq!(parse_first("if ((x) = 5) {}"), assignment_in_condition);
+        assert_eq!(
+            parse_first("if ((x = 5)) {}"),
+            Ok(Statement::ConditionalStatement(assignment, Block::new(vec![]), Box::new(None)))
+        );
+        assert!(parse_first("while ((x = 5)) {}").is_ok());
+    }
+
+    #[test]
+    fn it_should_reject_incrementing_a_literal() {
+        let mut parser = Parser::new(tokenize("++5; --\"a\"; (1)++; ++x; ++a.b; --a[0];"));
+        let result = parser.parse();
+        let invalid_prefix = Err(ParserError {
+            kind: ParserErrorKind::SyntaxError(Some(SyntaxErrorKind::InvalidLeftSidePrefix)),
+        });
+        assert_eq!(result[0], invalid_prefix);
+        assert_eq!(result[1], invalid_prefix);
+        assert_eq!(
+            result[2],
+            Err(ParserError {
+                kind: ParserErrorKind::SyntaxError(Some(SyntaxErrorKind::InvalidLeftSidePostfix)),
+            })
+        );
+        assert!(result[3..].iter().all(|statement| statement.is_ok()));
+    }
+
+    #[test]
+    fn it_should_reject_an_index_without_a_closing_bracket() {
+        let mut parser = Parser::new(tokenize("a[0; a[0 + 1; a[0];"));
+        let result = parser.parse();
+        let missing_bracket = Err(ParserError {
+            kind: ParserErrorKind::SyntaxError(Some(SyntaxErrorKind::UnexpectedToken(Token::Semicolon))),
+        });
+        assert_eq!(result[0], missing_bracket);
+        assert_eq!(result[1], missing_bracket);
+        assert!(result[2].is_ok());
+    }
+
+    #[test]
+    fn it_should_reject_unterminated_lists() {
+        for source in ["[1, 2", "f(1, 2"] {
+            let mut parser = Parser::new(tokenize(source));
+            let result = parser.parse();
+            assert_eq!(
+                result[0],
+                Err(ParserError {
+                    kind: ParserErrorKind::SyntaxError(Some(SyntaxErrorKind::UnexpectedToken(Token::EOF))),
+                }),
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn it_should_reject_list_elements_without_a_comma_between_them() {
+        let mut parser = Parser::new(tokenize("[1 2]; f(1 2); [1, 2];"));
+        let result = parser.parse();
+        let missing_comma = Err(ParserError {
+            kind: ParserErrorKind::SyntaxError(Some(SyntaxErrorKind::UnexpectedToken(Token::Number(2.0)))),
+        });
+        assert_eq!(result[0], missing_comma);
+        assert_eq!(result[1], missing_comma);
+        assert!(result[2].is_ok());
+    }
+
+    #[test]
+    fn it_should_reject_empty_list_elements() {
+        let mut parser = Parser::new(tokenize("[1,,2]; f(1,,2); [,1]; f(,); [1, 2,]; f(1, 2,);"));
+        let result = parser.parse();
+        let empty_element = Err(ParserError {
+            kind: ParserErrorKind::SyntaxError(Some(SyntaxErrorKind::UnexpectedToken(Token::Comma))),
+        });
+        assert_eq!(result[0], empty_element);
+        assert_eq!(result[1], empty_element);
+        assert_eq!(result[2], empty_element);
+        assert_eq!(result[3], empty_element);
+        assert!(result[4..].iter().all(|statement| statement.is_ok()));
+    }
+
+    #[test]
+    fn it_should_reject_an_object_literal_it_cannot_read_to_the_end() {
+        let mut parser = Parser::new(tokenize("let o = {a: 1 b: 2}; let p = {a: 1, +: 2}; let q = {\"\": 1, a: 2,}; let r = {a: 1"));
+        let result = parser.parse();
+        let syntax_error = |kind: SyntaxErrorKind| Err(ParserError {
+            kind: ParserErrorKind::SyntaxError(Some(kind)),
+        });
+        assert_eq!(result[0], syntax_error(SyntaxErrorKind::UnexpectedIdentifier("b".into())));
+        assert_eq!(result[1], syntax_error(SyntaxErrorKind::UnexpectedToken(Token::Plus)));
+        assert!(result[2].is_ok());
+        assert_eq!(result[3], syntax_error(SyntaxErrorKind::UnexpectedToken(Token::EOF)));
+    }
+
+    #[test]
+    fn it_should_read_keywords_as_property_names() {
+        let mut parser = Parser::new(tokenize("o.default; o.catch(); let p = {for: 1, delete() {}}; o.; let q = {default};"));
+        let result = parser.parse();
+        let object = || Box::new(Expression::Identifier("o".into()));
+        assert_eq!(result[0], Ok(Statement::ExpressionStatement(Expression::Member(object(), "default".into()))));
+        assert_eq!(
+            result[1],
+            Ok(Statement::ExpressionStatement(Expression::Call(Box::new(Expression::Member(object(), "catch".into())), vec![])))
+        );
+        assert!(result[2].is_ok());
+        let syntax_error = |token: Token| Err(ParserError {
+            kind: ParserErrorKind::SyntaxError(Some(SyntaxErrorKind::UnexpectedToken(token))),
+        });
+        assert_eq!(result[3], syntax_error(Token::Semicolon));
+        assert_eq!(result[4], syntax_error(Token::Default));
+    }
 }
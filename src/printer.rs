@@ -0,0 +1,625 @@
+//! Turns the AST back into readable source code, mostly so failing parser tests are easy to read.
+//! Parentheses are only written where they are needed to keep the original precedence.
+
+use std::fmt::{Display, Formatter, Result};
+
+use crate::ast::{Block, Expression, Operator, Pattern, PostfixOperator, PrefixOperator, Statement};
+use crate::interpreter::json::quote;
+
+const INDENT: &str = "    ";
+
+// Precedence levels, matching the table at the top of parser.rs
+const ASSIGNMENT: u8 = 2;
+const PREFIX: u8 = 14;
+const POSTFIX: u8 = 15;
+const CALL: u8 = 17;
+const PRIMARY: u8 = 18;
+
+fn operator_precedence(operator: &Operator) -> u8 {
+    match operator {
+        Operator::Or | Operator::NullishCoalescing => 3,
+        Operator::And => 4,
+        Operator::Equal => 8,
+        Operator::LessThan
+        | Operator::GreaterThan
+        | Operator::LessThanOrEqual
+        | Operator::GreaterThanOrEqual
+        | Operator::In => 9,
+        Operator::Add | Operator::Subtract => 11,
+        Operator::Multiply | Operator::Divide | Operator::Modulo => 12,
+        Operator::Exponentiation => 13,
+    }
+}
+
+fn operator_symbol(operator: &Operator) -> &'static str {
+    match operator {
+        Operator::Add => "+",
+        Operator::Subtract => "-",
+        Operator::Multiply => "*",
+        Operator::Divide => "/",
+        Operator::Equal => "==",
+        Operator::LessThan => "<",
+        Operator::GreaterThan => ">",
+        Operator::LessThanOrEqual => "<=",
+        Operator::GreaterThanOrEqual => ">=",
+        Operator::And => "&&",
+        Operator::Or => "||",
+        Operator::Exponentiation => "**",
+        Operator::Modulo => "%",
+        Operator::NullishCoalescing => "??",
+        Operator::In => "in",
+    }
+}
+
+fn prefix_symbol(operator: &PrefixOperator) -> &'static str {
+    match operator {
+        PrefixOperator::Increment => "++",
+        PrefixOperator::Decrement => "--",
+        PrefixOperator::Negative => "-",
+        PrefixOperator::Positive => "+",
+        PrefixOperator::Not => "!",
+        PrefixOperator::TypeOf => "typeof ",
+        PrefixOperator::Delete => "delete ",
+    }
+}
+
+fn precedence(expression: &Expression) -> u8 {
+    match expression {
+        Expression::Operation(_, operator, _) => operator_precedence(operator),
+        Expression::Assignment(_, _)
+        | Expression::LogicalAssignment(_, _, _)
+        | Expression::CompoundAssignment(_, _, _)
+        | Expression::Conditional(_, _, _)
+        | Expression::ArrowFunction(_, _)
+        | Expression::Spread(_) => ASSIGNMENT,
+        Expression::Prefix(_, _) => PREFIX,
+        Expression::Postfix(_, _) => POSTFIX,
+        Expression::Call(_, _) | Expression::Member(_, _) | Expression::Index(_, _) => CALL,
+        // negative numbers print with a minus sign, so they bind like a prefix
+        Expression::NumberLiteral(number) if *number < 0.0 => PREFIX,
+        _ => PRIMARY,
+    }
+}
+
+// ?? can't be mixed with || or && without parentheses, whichever of them is nested inside the other
+fn mixes_nullish_with_logical(operator: &Operator, child: &Expression) -> bool {
+    let Expression::Operation(_, child_operator, _) = child else {
+        return false;
+    };
+    let is_logical = |operator: &Operator| matches!(operator, Operator::Or | Operator::And);
+    (*operator == Operator::NullishCoalescing && is_logical(child_operator))
+        || (is_logical(operator) && *child_operator == Operator::NullishCoalescing)
+}
+
+// The operand an expression's source starts with, like the object of a.b().c
+fn leading_operand(expression: &Expression) -> &Expression {
+    match expression {
+        Expression::Call(inner, _)
+        | Expression::Member(inner, _)
+        | Expression::Index(inner, _)
+        | Expression::Postfix(inner, _)
+        | Expression::Operation(inner, _, _)
+        | Expression::Assignment(inner, _)
+        | Expression::LogicalAssignment(inner, _, _)
+        | Expression::CompoundAssignment(inner, _, _)
+        | Expression::Conditional(inner, _, _) => leading_operand(inner),
+        _ => expression,
+    }
+}
+
+fn write_wrapped(f: &mut Formatter, expression: &Expression, needs_parens: bool) -> Result {
+    if needs_parens {
+        write!(f, "({})", expression)
+    } else {
+        write!(f, "{}", expression)
+    }
+}
+
+fn write_list<T: Display>(f: &mut Formatter, items: &[T]) -> Result {
+    for (index, item) in items.iter().enumerate() {
+        if index > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", item)?;
+    }
+    Ok(())
+}
+
+fn write_key(f: &mut Formatter, key: &str) -> Result {
+    let is_identifier = key
+        .chars()
+        .next()
+        .is_some_and(|first| first.is_alphabetic() || first == '_' || first == '$')
+        && key
+            .chars()
+            .all(|character| character.is_alphanumeric() || character == '_' || character == '$');
+    if is_identifier {
+        write!(f, "{}", key)
+    } else {
+        write!(f, "{}", quote(key))
+    }
+}
+
+impl Display for Expression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            Expression::NumberLiteral(number) => write!(f, "{}", number),
+            Expression::Boolean(value) => write!(f, "{}", value),
+            Expression::Null => write!(f, "null"),
+            Expression::Undefined => write!(f, "undefined"),
+            Expression::This => write!(f, "this"),
+            Expression::Function(name, parameters, block) => {
+                write!(f, "function ")?;
+                write_method(f, name, parameters, block)
+            }
+            Expression::ArrowFunction(parameters, block) => {
+                write!(f, "(")?;
+                write_list(f, parameters)?;
+                write!(f, ") => ")?;
+                write_block(f, block, 0)
+            }
+            Expression::Identifier(identifier) => write!(f, "{}", identifier),
+            Expression::String(string) => write!(f, "{}", quote(string)),
+            Expression::Prefix(operator, operand) => {
+                let symbol = prefix_symbol(operator);
+                let operand_source = operand.to_string();
+                // -(-x) would read as a decrement without its parentheses
+                let merges_with_operand = operand_source.starts_with(['-', '+'])
+                    && symbol.ends_with(&operand_source[..1]);
+                if precedence(operand) < PREFIX || merges_with_operand {
+                    write!(f, "{}({})", symbol, operand_source)
+                } else {
+                    write!(f, "{}{}", symbol, operand_source)
+                }
+            }
+            Expression::Postfix(operand, operator) => {
+                write_wrapped(f, operand, precedence(operand) < CALL)?;
+                match operator {
+                    PostfixOperator::Increment => write!(f, "++"),
+                    PostfixOperator::Decrement => write!(f, "--"),
+                }
+            }
+            Expression::Operation(left, operator, right) => {
+                let own = operator_precedence(operator);
+                // ** groups right to left and everything else left to right, so a child with the same
+                // precedence only needs parentheses on the side it doesn't group towards.
+                // A prefix on the left of ** is a syntax error without parentheses.
+                let (left_parens, right_parens) = if *operator == Operator::Exponentiation {
+                    (precedence(left) <= own || precedence(left) == PREFIX, precedence(right) < own)
+                } else {
+                    (precedence(left) < own, precedence(right) <= own)
+                };
+                let left_parens = left_parens || mixes_nullish_with_logical(operator, left);
+                let right_parens = right_parens || mixes_nullish_with_logical(operator, right);
+                write_wrapped(f, left, left_parens)?;
+                write!(f, " {} ", operator_symbol(operator))?;
+                write_wrapped(f, right, right_parens)
+            }
+            Expression::Assignment(target, value) => {
+                write_wrapped(f, target, precedence(target) <= ASSIGNMENT)?;
+                write!(f, " = ")?;
+                write_wrapped(f, value, precedence(value) < ASSIGNMENT)
+            }
+            Expression::LogicalAssignment(target, operator, value)
+            | Expression::CompoundAssignment(target, operator, value) => {
+                write_wrapped(f, target, precedence(target) <= ASSIGNMENT)?;
+                write!(f, " {}= ", operator_symbol(operator))?;
+                write_wrapped(f, value, precedence(value) < ASSIGNMENT)
+            }
+            Expression::Conditional(condition, consequent, alternate) => {
+                write_wrapped(f, condition, precedence(condition) <= ASSIGNMENT)?;
+                write!(f, " ? ")?;
+                write_wrapped(f, consequent, precedence(consequent) < ASSIGNMENT)?;
+                write!(f, " : ")?;
+                write_wrapped(f, alternate, precedence(alternate) < ASSIGNMENT)
+            }
+            Expression::Call(callee, arguments) => {
+                write_wrapped(f, callee, precedence(callee) < CALL)?;
+                write!(f, "(")?;
+                write_list(f, arguments)?;
+                write!(f, ")")
+            }
+            Expression::Array(elements) => {
+                write!(f, "[")?;
+                write_list(f, elements)?;
+                write!(f, "]")
+            }
+            Expression::Spread(inner) => write!(f, "...{}", inner),
+            // always wrapped, as the commas would otherwise read as separate arguments or elements
+            Expression::Sequence(expressions) => {
+                write!(f, "(")?;
+                write_list(f, expressions)?;
+                write!(f, ")")
+            }
+            Expression::Member(object, property) => {
+                // the dot after 1 would read as a decimal point
+                let is_number = matches!(**object, Expression::NumberLiteral(_));
+                write_wrapped(f, object, precedence(object) < CALL || is_number)?;
+                write!(f, ".{}", property)
+            }
+            Expression::Index(object, index) => {
+                write_wrapped(f, object, precedence(object) < CALL)?;
+                write!(f, "[{}]", index)
+            }
+            Expression::Object(properties) => {
+                if properties.is_empty() {
+                    return write!(f, "{{}}");
+                }
+                write!(f, "{{ ")?;
+                for (index, (key, value)) in properties.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    match value {
+                        Expression::Function(name, parameters, block) if name == key => {
+                            write_method(f, key, parameters, block)?
+                        }
+                        _ => {
+                            write_key(f, key)?;
+                            write!(f, ": {}", value)?;
+                        }
+                    }
+                }
+                write!(f, " }}")
+            }
+        }
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            Pattern::Identifier(identifier) => write!(f, "{}", identifier),
+            Pattern::Array(elements) => {
+                write!(f, "[")?;
+                write_list(f, elements)?;
+                write!(f, "]")
+            }
+            Pattern::Object(properties) => {
+                write!(f, "{{ ")?;
+                for (index, (key, pattern)) in properties.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    match pattern {
+                        Pattern::Identifier(identifier) if identifier == key => write!(f, "{}", key)?,
+                        _ => {
+                            write_key(f, key)?;
+                            write!(f, ": {}", pattern)?;
+                        }
+                    }
+                }
+                write!(f, " }}")
+            }
+        }
+    }
+}
+
+// Writes name(parameters) { ... }, the part a function expression and an object method have in common
+fn write_method(f: &mut Formatter, name: &str, parameters: &[Expression], block: &Block) -> Result {
+    write!(f, "{}(", name)?;
+    write_list(f, parameters)?;
+    write!(f, ") ")?;
+    write_block(f, block, 0)
+}
+
+fn write_block(f: &mut Formatter, block: &Block, depth: usize) -> Result {
+    writeln!(f, "{{")?;
+    for statement in block.statements() {
+        write_statement(f, statement, depth + 1)?;
+        writeln!(f)?;
+    }
+    write!(f, "{}}}", INDENT.repeat(depth))
+}
+
+// The indentation is only written before the statement, as the caller decides what follows it
+fn write_statement(f: &mut Formatter, statement: &Statement, depth: usize) -> Result {
+    write!(f, "{}", INDENT.repeat(depth))?;
+    write_unindented_statement(f, statement, depth)
+}
+
+// Writes a statement that continues the current line, like the if of an else if
+fn write_unindented_statement(f: &mut Formatter, statement: &Statement, depth: usize) -> Result {
+    match statement {
+        Statement::Let(identifier, Expression::Undefined) => write!(f, "let {};", identifier),
+        Statement::Let(identifier, expression) => write!(f, "let {} = {};", identifier, expression),
+        Statement::LetPattern(pattern, expression) => write!(f, "let {} = {};", pattern, expression),
+        Statement::ExpressionStatement(expression) => write_expression_statement(f, expression),
+        Statement::ReturnStatement(Some(expression)) => write!(f, "return {};", expression),
+        Statement::ReturnStatement(None) => write!(f, "return;"),
+        Statement::FunctionDeclaration(identifier, parameters, block) => {
+            write!(f, "function {}(", identifier)?;
+            write_list(f, parameters)?;
+            write!(f, ") ")?;
+            write_block(f, block, depth)
+        }
+        Statement::ConditionalStatement(condition, block, else_branch) => {
+            write!(f, "if ({}) ", condition)?;
+            write_block(f, block, depth)?;
+            match else_branch.as_ref() {
+                // a plain else is stored as a conditional that is always true
+                Some(Statement::ConditionalStatement(Expression::Boolean(true), block, next)) if next.is_none() => {
+                    write!(f, " else ")?;
+                    write_block(f, block, depth)
+                }
+                Some(else_if) => {
+                    write!(f, " else ")?;
+                    write_unindented_statement(f, else_if, depth)
+                }
+                None => Ok(()),
+            }
+        }
+        Statement::While(conditional) => match conditional.as_ref() {
+            Statement::ConditionalStatement(condition, block, _) => {
+                write!(f, "while ({}) ", condition)?;
+                write_block(f, block, depth)
+            }
+            other => write!(f, "while (true) {{ {} }}", other),
+        },
+        Statement::For(init, condition, update, block) => {
+            let init = init.as_ref().map_or(";".to_string(), |init| init.to_string());
+            let condition = condition.as_ref().map_or(String::new(), |condition| format!(" {}", condition));
+            let update = update.as_ref().map_or(String::new(), |update| format!(" {}", update));
+            write!(f, "for ({}{};{}) ", init, condition, update)?;
+            write_block(f, block, depth)
+        }
+        Statement::Break => write!(f, "break;"),
+        Statement::Continue => write!(f, "continue;"),
+        Statement::Throw(expression) => write!(f, "throw {};", expression),
+        Statement::Switch(discriminant, cases) => {
+            writeln!(f, "switch ({}) {{", discriminant)?;
+            for (test, statements) in cases {
+                write!(f, "{}", INDENT.repeat(depth + 1))?;
+                match test {
+                    Some(test) => writeln!(f, "case {}:", test)?,
+                    None => writeln!(f, "default:")?,
+                }
+                for statement in statements {
+                    write_statement(f, statement, depth + 2)?;
+                    writeln!(f)?;
+                }
+            }
+            write!(f, "{}}}", INDENT.repeat(depth))
+        }
+        Statement::Block(block) => write_block(f, block, depth),
+        Statement::Try(block, catch_clause, finally_block) => {
+            write!(f, "try ")?;
+            write_block(f, block, depth)?;
+            if let Some((identifier, catch_block)) = catch_clause {
+                match identifier {
+                    Some(identifier) => write!(f, " catch ({}) ", identifier)?,
+                    None => write!(f, " catch ")?,
+                }
+                write_block(f, catch_block, depth)?;
+            }
+            if let Some(finally_block) = finally_block {
+                write!(f, " finally ")?;
+                write_block(f, finally_block, depth)?;
+            }
+            Ok(())
+        }
+        Statement::ForOf(identifier, iterable, block) => {
+            write!(f, "for (let {} of {}) ", identifier, iterable)?;
+            write_block(f, block, depth)
+        }
+    }
+}
+
+// An object literal or function at the start of a statement would read as a block or a declaration without
+// its parentheses, which it needs even when it only leads the statement, like ({}).a or (function () {})()
+fn write_expression_statement(f: &mut Formatter, expression: &Expression) -> Result {
+    let source = expression.to_string();
+    let leading = leading_operand(expression);
+    let leading_source = leading.to_string();
+    // a leading operand that is already wrapped doesn't start the source
+    if matches!(leading, Expression::Object(_) | Expression::Function(_, _, _)) && source.starts_with(&leading_source) {
+        write!(f, "({}){};", leading_source, &source[leading_source.len()..])
+    } else {
+        write!(f, "{};", source)
+    }
+}
+
+impl Display for Statement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write_statement(f, self, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lexer::tokenize;
+    use crate::parser::{separate_out_statements_and_parser_errors, Parser};
+
+    use super::*;
+
+    fn number(value: f64) -> Box<Expression> {
+        Box::new(Expression::NumberLiteral(value))
+    }
+
+    // Parses the source and prints it back out, one statement per line
+    fn round_trip(source: &str) -> String {
+        let mut parser = Parser::new(tokenize(source));
+        let (statements, errors) = separate_out_statements_and_parser_errors(parser.parse());
+        assert_eq!(errors.len(), 0);
+        statements
+            .iter()
+            .map(|statement| statement.to_string())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn it_should_not_add_redundant_parens() {
+        let expression = Expression::Operation(
+            number(5.0),
+            Operator::Add,
+            Box::new(Expression::Operation(number(2.0), Operator::Multiply, number(3.0))),
+        );
+        assert_eq!(expression.to_string(), "5 + 2 * 3");
+    }
+
+    #[test]
+    fn it_should_keep_parens_that_change_precedence() {
+        let expression = Expression::Operation(
+            Box::new(Expression::Operation(number(5.0), Operator::Add, number(2.0))),
+            Operator::Multiply,
+            number(3.0),
+        );
+        assert_eq!(expression.to_string(), "(5 + 2) * 3");
+    }
+
+    #[test]
+    fn it_should_keep_parens_on_the_right_of_left_associative_operators() {
+        assert_eq!(round_trip("a - (b - c);"), "a - (b - c);");
+        assert_eq!(round_trip("a - b - c;"), "a - b - c;");
+    }
+
+    #[test]
+    fn it_should_keep_parens_on_the_left_of_exponentiation() {
+        assert_eq!(round_trip("(2 ** 3) ** 2;"), "(2 ** 3) ** 2;");
+        assert_eq!(round_trip("2 ** 3 ** 2;"), "2 ** 3 ** 2;");
+    }
+
+    #[test]
+    fn it_should_separate_nested_negation() {
+        let expression = Expression::Prefix(
+            PrefixOperator::Negative,
+            Box::new(Expression::Prefix(PrefixOperator::Negative, Box::new(Expression::Identifier("x".into())))),
+        );
+        assert_eq!(expression.to_string(), "-(-x)");
+        assert_eq!(round_trip("!!x;"), "!!x;");
+    }
+
+    #[test]
+    fn it_should_print_literals_and_member_access() {
+        assert_eq!(
+            round_trip("let x = { a: [1, \"two\", null], \"b c\": f(...args).length };"),
+            "let x = { a: [1, \"two\", null], \"b c\": f(...args).length };"
+        );
+    }
+
+    #[test]
+    fn it_should_indent_blocks() {
+        let source = "function f(a) { if (a < 1) { return a; } else if (a > 5) { return 5; } else { return 0; } }";
+        let expected = [
+            "function f(a) {",
+            "    if (a < 1) {",
+            "        return a;",
+            "    } else if (a > 5) {",
+            "        return 5;",
+            "    } else {",
+            "        return 0;",
+            "    }",
+            "}",
+        ]
+        .join("\n");
+        assert_eq!(round_trip(source), expected);
+    }
+
+    #[test]
+    fn it_should_print_loops() {
+        assert_eq!(
+            round_trip("while (x < 3) { x = x + 1; } for (let i of [1]) { x; }"),
+            "while (x < 3) {\n    x = x + 1;\n}\nfor (let i of [1]) {\n    x;\n}"
+        );
+    }
+
+    #[test]
+    fn it_should_print_c_style_for_loops() {
+        assert_eq!(round_trip("for (;;) { break; }"), "for (;;) {\n    break;\n}");
+        assert_eq!(
+            round_trip("for (let i = 0; i < 3; i = i + 1) { i; }"),
+            "for (let i = 0; i < 3; i = i + 1) {\n    i;\n}"
+        );
+    }
+
+    #[test]
+    fn it_should_print_logical_assignment() {
+        assert_eq!(round_trip("x &&= y ?? z;"), "x &&= y ?? z;");
+    }
+
+    #[test]
+    fn it_should_print_compound_assignment() {
+        assert_eq!(round_trip("arr[i] **= 2 + 1;"), "arr[i] **= 2 + 1;");
+    }
+
+    #[test]
+    fn it_should_keep_object_literal_statements_apart_from_blocks() {
+        assert_eq!(round_trip("({ a: 1 }); { a; }"), "({ a: 1 });\n{\n    a;\n}");
+    }
+
+    #[test]
+    fn it_should_wrap_an_object_or_function_leading_a_statement() {
+        assert_eq!(round_trip("({}).a;"), "({}).a;");
+        assert_eq!(round_trip("({ a: 1 }).a = 2;"), "({ a: 1 }).a = 2;");
+        assert_eq!(round_trip("(function () {})();"), "(function () {\n})();");
+        assert_eq!(round_trip("let f = function () {}.length;"), "let f = function () {\n}.length;");
+    }
+
+    #[test]
+    fn it_should_wrap_numbers_before_a_dot() {
+        assert_eq!(round_trip("(1).toString(16);"), "(1).toString(16);");
+        assert_eq!(round_trip("[1][0];"), "[1][0];");
+    }
+
+    #[test]
+    fn it_should_keep_parens_between_nullish_coalescing_and_logical_operators() {
+        assert_eq!(round_trip("(a || b) ?? c;"), "(a || b) ?? c;");
+        assert_eq!(round_trip("a ?? (b && c);"), "a ?? (b && c);");
+        assert_eq!(round_trip("(a ?? b) || c;"), "(a ?? b) || c;");
+        assert_eq!(round_trip("a ?? b ?? c;"), "a ?? b ?? c;");
+    }
+
+    #[test]
+    fn it_should_print_destructuring() {
+        assert_eq!(round_trip("let [a, { b, c: [d] }] = x;"), "let [a, { b, c: [d] }] = x;");
+    }
+
+    #[test]
+    fn it_should_print_try_catch() {
+        assert_eq!(
+            round_trip("try { f(); } catch (e) { e; } try { f(); } catch { }"),
+            "try {\n    f();\n} catch (e) {\n    e;\n}\ntry {\n    f();\n} catch {\n}"
+        );
+    }
+
+    #[test]
+    fn it_should_print_finally() {
+        assert_eq!(
+            round_trip("try { f(); } finally { g(); } try { f(); } catch (e) { } finally { }"),
+            "try {\n    f();\n} finally {\n    g();\n}\ntry {\n    f();\n} catch (e) {\n} finally {\n}"
+        );
+    }
+
+    #[test]
+    fn it_should_print_conditionals() {
+        assert_eq!(round_trip("x = a || b ? c = 1 : d ? 2 : 3;"), "x = a || b ? c = 1 : d ? 2 : 3;");
+        assert_eq!(round_trip("(a ? b : c) ? d : e;"), "(a ? b : c) ? d : e;");
+    }
+
+    #[test]
+    fn it_should_print_switch() {
+        assert_eq!(
+            round_trip("switch (x) { case \"a\": f(); break; default: g(); }"),
+            "switch (x) {\n    case \"a\":\n        f();\n        break;\n    default:\n        g();\n}"
+        );
+    }
+
+    #[test]
+    fn it_should_print_methods() {
+        assert_eq!(
+            round_trip("({ x: 1, get() { return this.x; } });"),
+            "({ x: 1, get() {\n    return this.x;\n} });"
+        );
+    }
+
+    #[test]
+    fn it_should_print_throw() {
+        assert_eq!(round_trip("throw \"bad \" + x;"), "throw \"bad \" + x;");
+    }
+
+    #[test]
+    fn it_should_print_uninitialized_let_and_typeof() {
+        assert_eq!(round_trip("let x; typeof -x == \"number\";"), "let x;\ntypeof -x == \"number\";");
+    }
+}